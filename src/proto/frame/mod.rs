@@ -0,0 +1,194 @@
+use std::io::{Read, Write};
+
+pub const VERSION: u8 = 1;
+
+// legacy 5 byte sequences always start with a request discriminant, which never reaches this value
+pub const MAGIC: u8 = 0xfa;
+
+const MAX_PAYLOAD: u32 = 1 << 20;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Hello,
+    Sequence,
+    Reply,
+    Notification,
+}
+
+impl TryFrom<u8> for Opcode {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(byte: u8) -> Result<Opcode, Self::Error> {
+        match byte {
+            0 => Ok(Opcode::Hello),
+            1 => Ok(Opcode::Sequence),
+            2 => Ok(Opcode::Reply),
+            3 => Ok(Opcode::Notification),
+            _ => Err(format!("unknown opcode: {}", byte).into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Header {
+    pub version: u8,
+    pub opcode: Opcode,
+    pub length: u32,
+}
+
+impl Header {
+    pub const SIZE: usize = 7;
+
+    pub fn encode(&self) -> [u8; Header::SIZE] {
+        let length = self.length.to_le_bytes();
+
+        [
+            MAGIC,
+            self.version,
+            self.opcode as u8,
+            length[0],
+            length[1],
+            length[2],
+            length[3],
+        ]
+    }
+
+    pub fn decode(bytes: [u8; Header::SIZE]) -> Result<Header, Box<dyn std::error::Error>> {
+        if bytes[0] != MAGIC {
+            return Err(format!("invalid frame magic: {:#x}", bytes[0]).into());
+        }
+
+        let length = u32::from_le_bytes([bytes[3], bytes[4], bytes[5], bytes[6]]);
+
+        if length > MAX_PAYLOAD {
+            return Err(format!("frame payload too large: {}", length).into());
+        }
+
+        Ok(Header {
+            version: bytes[1],
+            opcode: Opcode::try_from(bytes[2])?,
+            length,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub header: Header,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new(opcode: Opcode, payload: Vec<u8>) -> Frame {
+        Frame {
+            header: Header {
+                version: VERSION,
+                opcode,
+                length: payload.len() as u32,
+            },
+            payload,
+        }
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), Box<dyn std::error::Error>> {
+        let mut bytes = self.header.encode().to_vec();
+
+        bytes.extend(&self.payload);
+
+        writer.write_all(&bytes).map_err(|err| err.into())
+    }
+
+    pub fn read_after_magic(reader: &mut impl Read) -> Result<Frame, Box<dyn std::error::Error>> {
+        let mut bytes = [MAGIC; Header::SIZE];
+
+        reader.read_exact(&mut bytes[1..])?;
+
+        let header = Header::decode(bytes)?;
+        let mut payload = vec![0; header.length as usize];
+
+        reader.read_exact(&mut payload)?;
+
+        Ok(Frame { header, payload })
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Option<Frame>, Box<dyn std::error::Error>> {
+        let mut magic = [0; 1];
+
+        match reader.read(&mut magic)? {
+            0 => Ok(None),
+            _ if magic[0] != MAGIC => Err(format!("invalid frame magic: {:#x}", magic[0]).into()),
+            _ => Frame::read_after_magic(reader).map(Some),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Writer {
+        Writer { bytes: Vec::new() }
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    pub fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+
+        self.bytes.extend(value.as_bytes());
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Box<dyn std::error::Error>> {
+        if self.bytes.len() < len {
+            return Err("truncated payload".into());
+        }
+
+        let (bytes, rest) = self.bytes.split_at(len);
+
+        self.bytes = rest;
+
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Box<dyn std::error::Error>> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Box<dyn std::error::Error>> {
+        self.take(4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn string(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let len = self.u32()? as usize;
+
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|err| err.into())
+    }
+}
@@ -1,43 +1,117 @@
+mod frame;
+
+pub use frame::{Frame, Header, Opcode, Reader, Writer, MAGIC, VERSION};
+
 use std::env;
-use std::io::{Read, Write};
-use std::mem;
+use std::io::Read;
+use std::net::Shutdown;
 use std::os::unix::net::UnixStream;
-use std::ptr;
-use std::slice;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(u32),
+    String(String),
+}
 
-#[repr(packed, C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Sequence {
     pub request: Request,
-    pub value: u32,
+    pub values: Vec<Value>,
 }
 
 impl Sequence {
+    pub const LEGACY_SIZE: usize = 5;
+
     pub fn new(request: Request, value: u32) -> Sequence {
-        Sequence { request, value }
+        Sequence::with_values(request, vec![Value::Integer(value)])
     }
 
-    pub fn decode<'a>(bytes: &'a [u8]) -> Sequence {
-        unsafe {
-            assert_eq!(bytes.len(), mem::size_of::<Sequence>());
+    pub fn with_values(request: Request, values: Vec<Value>) -> Sequence {
+        Sequence { request, values }
+    }
 
-            ptr::read(bytes.as_ptr() as *const Sequence)
-        }
+    pub fn value(&self) -> u32 {
+        self.values
+            .iter()
+            .find_map(|value| match value {
+                Value::Integer(integer) => Some(*integer),
+                Value::String(_) => None,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn integer(&self, index: usize) -> Option<u32> {
+        self.values
+            .iter()
+            .filter_map(|value| match value {
+                Value::Integer(integer) => Some(*integer),
+                Value::String(_) => None,
+            })
+            .nth(index)
+    }
+
+    pub fn string(&self) -> Option<&str> {
+        self.values.iter().find_map(|value| match value {
+            Value::String(string) => Some(string.as_str()),
+            Value::Integer(_) => None,
+        })
+    }
+
+    pub fn decode_legacy(bytes: &[u8]) -> Sequence {
+        assert_eq!(bytes.len(), Sequence::LEGACY_SIZE);
+
+        Sequence::new(
+            Request::from(bytes[0]),
+            u32::from_ne_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]),
+        )
     }
 
     pub fn encode(&self) -> Vec<u8> {
-        unsafe {
-            slice::from_raw_parts(
-                (self as *const Sequence) as *const u8,
-                mem::size_of::<Sequence>(),
-            )
-            .to_vec()
+        let mut writer = Writer::new();
+
+        writer.u8(self.request as u8);
+
+        for value in &self.values {
+            match value {
+                Value::Integer(integer) => {
+                    writer.u8(0);
+                    writer.u32(*integer);
+                }
+                Value::String(string) => {
+                    writer.u8(1);
+                    writer.string(string);
+                }
+            }
         }
+
+        writer.finish()
+    }
+
+    pub fn decode(frame: &Frame) -> Result<Sequence, Box<dyn std::error::Error>> {
+        if frame.header.opcode != Opcode::Sequence {
+            return Err(format!("expected sequence, found {:?}", frame.header.opcode).into());
+        }
+
+        let mut reader = Reader::new(&frame.payload);
+        let request = Request::from(reader.u8()?);
+        let mut values = Vec::new();
+
+        while !reader.is_empty() {
+            match reader.u8()? {
+                0 => values.push(Value::Integer(reader.u32()?)),
+                1 => values.push(Value::String(reader.string()?)),
+                kind => return Err(format!("unknown value kind: {}", kind).into()),
+            }
+        }
+
+        Ok(Sequence { request, values })
     }
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Request {
     Workspace,
     Kill,
@@ -67,9 +141,370 @@ pub enum Request {
     WorkspacePerMonitor,
     MonitorCirculate,
     Quit,
+    QueryFocused,
+    QueryWorkspaces,
+    QueryClients,
+    QueryConfig,
+    Subscribe,
+    Layout,
+    LayoutNext,
+    MasterRatio,
+    MasterRatioInc,
+    MasterRatioDec,
+    NMaster,
+    FullscreenToggle,
+    SendToWorkspace,
+    ToggleDocks,
+    Reload,
+    RuleAdd,
+    RuleRemove,
+    RuleList,
+    ScratchpadAdd,
+    ScratchpadToggle,
+    SwapUp,
+    SwapDown,
+    SwapMaster,
+    RotateStack,
+    FocusDir,
+    SwapDir,
+    FocusFloatToggle,
+    FocusScope,
+    FocusLast,
+    FocusCycleMru,
     Unknown,
 }
 
+impl Request {
+    // every request in discriminant order, so a byte off the wire indexes its request
+    pub const ALL: [Request; 58] = [
+        Request::Workspace,
+        Request::Kill,
+        Request::Close,
+        Request::PaddingTop,
+        Request::PaddingBottom,
+        Request::PaddingLeft,
+        Request::PaddingRight,
+        Request::WindowGaps,
+        Request::FocusedBorder,
+        Request::NormalBorder,
+        Request::BorderWidth,
+        Request::FocusUp,
+        Request::FocusDown,
+        Request::FocusMaster,
+        Request::FloatToggle,
+        Request::FloatLeft,
+        Request::FloatRight,
+        Request::FloatUp,
+        Request::FloatDown,
+        Request::ResizeLeft,
+        Request::ResizeRight,
+        Request::ResizeUp,
+        Request::ResizeDown,
+        Request::EnableMouse,
+        Request::DisableMouse,
+        Request::WorkspacePerMonitor,
+        Request::MonitorCirculate,
+        Request::Quit,
+        Request::QueryFocused,
+        Request::QueryWorkspaces,
+        Request::QueryClients,
+        Request::QueryConfig,
+        Request::Subscribe,
+        Request::Layout,
+        Request::LayoutNext,
+        Request::MasterRatio,
+        Request::MasterRatioInc,
+        Request::MasterRatioDec,
+        Request::NMaster,
+        Request::FullscreenToggle,
+        Request::SendToWorkspace,
+        Request::ToggleDocks,
+        Request::Reload,
+        Request::RuleAdd,
+        Request::RuleRemove,
+        Request::RuleList,
+        Request::ScratchpadAdd,
+        Request::ScratchpadToggle,
+        Request::SwapUp,
+        Request::SwapDown,
+        Request::SwapMaster,
+        Request::RotateStack,
+        Request::FocusDir,
+        Request::SwapDir,
+        Request::FocusFloatToggle,
+        Request::FocusScope,
+        Request::FocusLast,
+        Request::FocusCycleMru,
+    ];
+}
+
+impl From<u8> for Request {
+    fn from(byte: u8) -> Request {
+        Request::ALL.get(byte as usize).copied().unwrap_or(Request::Unknown)
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    InvalidArgument,
+    NoFocus,
+    Unsupported,
+    Failed,
+}
+
+impl From<u8> for Status {
+    fn from(byte: u8) -> Status {
+        match byte {
+            0 => Status::Ok,
+            1 => Status::InvalidArgument,
+            2 => Status::NoFocus,
+            3 => Status::Unsupported,
+            _ => Status::Failed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    Status(Status, String),
+    Focused(Option<u32>),
+    Workspaces(Vec<u32>),
+    Clients(Vec<u32>),
+    Config(Vec<(String, u32)>),
+    Rules(Vec<String>),
+}
+
+impl Reply {
+    pub fn ok() -> Reply {
+        Reply::Status(Status::Ok, String::new())
+    }
+
+    pub fn error(status: Status, message: impl Into<String>) -> Reply {
+        Reply::Status(status, message.into())
+    }
+
+    fn kind(&self) -> u8 {
+        match self {
+            Reply::Focused(_) => 0,
+            Reply::Workspaces(_) => 1,
+            Reply::Clients(_) => 2,
+            Reply::Config(_) => 3,
+            Reply::Status(..) => 4,
+            Reply::Rules(_) => 5,
+        }
+    }
+
+    fn decode_ids(reader: &mut Reader) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+        let mut ids = Vec::new();
+
+        while !reader.is_empty() {
+            ids.push(reader.u32()?);
+        }
+
+        Ok(ids)
+    }
+
+    fn decode_config(reader: &mut Reader) -> Result<Vec<(String, u32)>, Box<dyn std::error::Error>> {
+        let mut entries = Vec::new();
+
+        while !reader.is_empty() {
+            entries.push((reader.string()?, reader.u32()?));
+        }
+
+        Ok(entries)
+    }
+
+    fn decode_strings(reader: &mut Reader) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut strings = Vec::new();
+
+        while !reader.is_empty() {
+            strings.push(reader.string()?);
+        }
+
+        Ok(strings)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+
+        writer.u8(self.kind());
+
+        match self {
+            Reply::Status(status, message) => {
+                writer.u8(*status as u8);
+                writer.string(message);
+            }
+            Reply::Focused(focused) => focused.iter().for_each(|id| writer.u32(*id)),
+            Reply::Workspaces(ids) | Reply::Clients(ids) => ids.iter().for_each(|id| writer.u32(*id)),
+            Reply::Config(entries) => entries.iter().for_each(|(name, value)| {
+                writer.string(name);
+                writer.u32(*value);
+            }),
+            Reply::Rules(rules) => rules.iter().for_each(|rule| writer.string(rule)),
+        }
+
+        writer.finish()
+    }
+
+    pub fn decode(frame: &Frame) -> Result<Reply, Box<dyn std::error::Error>> {
+        if frame.header.opcode != Opcode::Reply {
+            return Err(format!("expected reply, found {:?}", frame.header.opcode).into());
+        }
+
+        let mut reader = Reader::new(&frame.payload);
+
+        match reader.u8()? {
+            0 => Ok(Reply::Focused(Reply::decode_ids(&mut reader)?.first().copied())),
+            1 => Ok(Reply::Workspaces(Reply::decode_ids(&mut reader)?)),
+            2 => Ok(Reply::Clients(Reply::decode_ids(&mut reader)?)),
+            3 => Ok(Reply::Config(Reply::decode_config(&mut reader)?)),
+            4 => Ok(Reply::Status(Status::from(reader.u8()?), reader.string()?)),
+            5 => Ok(Reply::Rules(Reply::decode_strings(&mut reader)?)),
+            kind => Err(format!("unknown reply kind: {}", kind).into()),
+        }
+    }
+}
+
+impl std::fmt::Display for Reply {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reply::Status(Status::Ok, _) => Ok(()),
+            Reply::Status(_, message) => writeln!(f, "{}", message),
+            Reply::Focused(Some(window)) => writeln!(f, "0x{:08x}", window),
+            Reply::Focused(None) => Ok(()),
+            Reply::Workspaces(workspaces) => workspaces
+                .iter()
+                .try_for_each(|workspace| writeln!(f, "{}", workspace)),
+            Reply::Clients(clients) => clients
+                .iter()
+                .try_for_each(|client| writeln!(f, "0x{:08x}", client)),
+            Reply::Config(entries) => entries
+                .iter()
+                .try_for_each(|(name, value)| writeln!(f, "{} {}", name, value)),
+            Reply::Rules(rules) => rules
+                .iter()
+                .enumerate()
+                .try_for_each(|(index, rule)| writeln!(f, "{} {}", index + 1, rule)),
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topic {
+    Focus = 1 << 0,
+    Workspace = 1 << 1,
+    Monitor = 1 << 2,
+    Client = 1 << 3,
+    Config = 1 << 4,
+}
+
+impl Topic {
+    pub const ALL: u32 = 0x1f;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    Focus(u32),
+    Workspace { monitor: u32, workspace: u32 },
+    Monitor { monitor: u32, window: u32 },
+    Map(u32),
+    Unmap(u32),
+    Config,
+}
+
+impl Notification {
+    pub fn topic(&self) -> Topic {
+        match self {
+            Notification::Focus(_) => Topic::Focus,
+            Notification::Workspace { .. } => Topic::Workspace,
+            Notification::Monitor { .. } => Topic::Monitor,
+            Notification::Map(_) | Notification::Unmap(_) => Topic::Client,
+            Notification::Config => Topic::Config,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+
+        match self {
+            Notification::Focus(window) => {
+                writer.u8(0);
+                writer.u32(*window);
+            }
+            Notification::Workspace { monitor, workspace } => {
+                writer.u8(1);
+                writer.u32(*monitor);
+                writer.u32(*workspace);
+            }
+            Notification::Monitor { monitor, window } => {
+                writer.u8(2);
+                writer.u32(*monitor);
+                writer.u32(*window);
+            }
+            Notification::Map(window) => {
+                writer.u8(3);
+                writer.u32(*window);
+            }
+            Notification::Unmap(window) => {
+                writer.u8(4);
+                writer.u32(*window);
+            }
+            Notification::Config => writer.u8(5),
+        }
+
+        writer.finish()
+    }
+
+    pub fn decode(frame: &Frame) -> Result<Notification, Box<dyn std::error::Error>> {
+        if frame.header.opcode != Opcode::Notification {
+            return Err(format!("expected notification, found {:?}", frame.header.opcode).into());
+        }
+
+        let mut reader = Reader::new(&frame.payload);
+
+        match reader.u8()? {
+            0 => Ok(Notification::Focus(reader.u32()?)),
+            1 => Ok(Notification::Workspace {
+                monitor: reader.u32()?,
+                workspace: reader.u32()?,
+            }),
+            2 => Ok(Notification::Monitor {
+                monitor: reader.u32()?,
+                window: reader.u32()?,
+            }),
+            3 => Ok(Notification::Map(reader.u32()?)),
+            4 => Ok(Notification::Unmap(reader.u32()?)),
+            5 => Ok(Notification::Config),
+            kind => Err(format!("unknown notification kind: {}", kind).into()),
+        }
+    }
+}
+
+impl std::fmt::Display for Notification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Notification::Focus(window) => write!(f, "focus 0x{:08x}", window),
+            Notification::Workspace { monitor, workspace } => {
+                write!(f, "workspace {} {}", monitor, workspace)
+            }
+            Notification::Monitor { monitor, window } => {
+                write!(f, "monitor {} 0x{:08x}", monitor, window)
+            }
+            Notification::Map(window) => write!(f, "map 0x{:08x}", window),
+            Notification::Unmap(window) => write!(f, "unmap 0x{:08x}", window),
+            Notification::Config => f.write_str("config"),
+        }
+    }
+}
+
+pub enum Protocol {
+    Framed,
+    Legacy(Vec<Sequence>),
+}
+
 pub struct Stream {
     stream: UnixStream,
 }
@@ -84,15 +519,120 @@ impl Stream {
     pub fn connect() -> Result<Stream, Box<dyn std::error::Error>> {
         let home = env::var("HOME")?;
 
+        Stream::connect_to(format!("{home}/.config/yaxiwm/ipc"))
+    }
+
+    pub fn connect_to(path: impl AsRef<Path>) -> Result<Stream, Box<dyn std::error::Error>> {
+        let mut stream = Stream {
+            stream: UnixStream::connect(path)?,
+        };
+
+        stream.handshake()?;
+
+        Ok(stream)
+    }
+
+    fn handshake(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        Frame::new(Opcode::Hello, Vec::new()).write(&mut self.stream)?;
+
+        let frame = self.frame()?.ok_or("connection closed during handshake")?;
+
+        if frame.header.opcode != Opcode::Hello {
+            return Err(format!("expected hello, found {:?}", frame.header.opcode).into());
+        }
+
+        if frame.header.version != VERSION {
+            return Err(format!(
+                "protocol version mismatch: client speaks {}, server speaks {}",
+                VERSION, frame.header.version
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    pub fn accept(&mut self) -> Result<Protocol, Box<dyn std::error::Error>> {
+        let mut first = [0; 1];
+
+        if self.stream.read(&mut first)? == 0 {
+            return Ok(Protocol::Legacy(Vec::new()));
+        }
+
+        if first[0] != MAGIC {
+            let mut bytes = first.to_vec();
+
+            bytes.extend(self.read()?);
+
+            return Ok(Protocol::Legacy(
+                bytes
+                    .chunks_exact(Sequence::LEGACY_SIZE)
+                    .map(Sequence::decode_legacy)
+                    .collect(),
+            ));
+        }
+
+        let frame = Frame::read_after_magic(&mut self.stream)?;
+
+        if frame.header.opcode != Opcode::Hello {
+            return Err(format!("expected hello, found {:?}", frame.header.opcode).into());
+        }
+
+        Frame::new(Opcode::Hello, Vec::new()).write(&mut self.stream)?;
+
+        if frame.header.version != VERSION {
+            return Err(format!(
+                "protocol version mismatch: client speaks {}, server speaks {}",
+                frame.header.version, VERSION
+            )
+            .into());
+        }
+
+        Ok(Protocol::Framed)
+    }
+
+    pub fn try_clone(&self) -> Result<Stream, Box<dyn std::error::Error>> {
         Ok(Stream {
-            stream: UnixStream::connect(format!("{home}/.config/yaxiwm/ipc"))?,
+            stream: self.stream.try_clone()?,
         })
     }
 
     pub fn send(&mut self, sequence: Sequence) -> Result<(), Box<dyn std::error::Error>> {
-        let bytes = sequence.encode();
+        Frame::new(Opcode::Sequence, sequence.encode()).write(&mut self.stream)
+    }
+
+    pub fn reply(&mut self, reply: Reply) -> Result<(), Box<dyn std::error::Error>> {
+        Frame::new(Opcode::Reply, reply.encode()).write(&mut self.stream)
+    }
+
+    pub fn notify(&mut self, notification: &Notification) -> Result<(), Box<dyn std::error::Error>> {
+        Frame::new(Opcode::Notification, notification.encode()).write(&mut self.stream)
+    }
+
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> Result<(), Box<dyn std::error::Error>> {
+        self.stream
+            .set_write_timeout(timeout)
+            .map_err(|err| err.into())
+    }
+
+    pub fn frame(&mut self) -> Result<Option<Frame>, Box<dyn std::error::Error>> {
+        Frame::read(&mut self.stream)
+    }
+
+    pub fn finish(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.stream
+            .shutdown(Shutdown::Write)
+            .map_err(|err| err.into())
+    }
+
+    pub fn replies(&mut self) -> Result<Vec<Reply>, Box<dyn std::error::Error>> {
+        let mut replies = Vec::new();
+
+        while let Some(frame) = self.frame()? {
+            replies.push(Reply::decode(&frame)?);
+        }
 
-        self.stream.write_all(&bytes).map_err(|err| err.into())
+        Ok(replies)
     }
 
     pub fn read(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
@@ -104,3 +644,103 @@ impl Stream {
             .map(|_| buffer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    fn round_trip(frame: Frame) -> Frame {
+        let mut bytes = Vec::new();
+
+        frame.write(&mut bytes).unwrap();
+
+        Frame::read(&mut bytes.as_slice()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let sequence = Sequence::with_values(
+            Request::RuleAdd,
+            vec![Value::String(String::from("class=\"Gimp 2\" state=float"))],
+        );
+
+        let decoded = Sequence::decode(&round_trip(Frame::new(Opcode::Sequence, sequence.encode()))).unwrap();
+
+        assert_eq!(decoded.request, Request::RuleAdd);
+        assert_eq!(decoded.values, sequence.values);
+
+        let sequence = Sequence::with_values(
+            Request::MasterRatio,
+            vec![Value::Integer(0), Value::Integer(1), Value::Integer(u32::MAX)],
+        );
+
+        let decoded = Sequence::decode(&round_trip(Frame::new(Opcode::Sequence, sequence.encode()))).unwrap();
+
+        assert_eq!(decoded.request, Request::MasterRatio);
+        assert_eq!(decoded.values, sequence.values);
+
+        let reply = Reply::Workspaces(vec![1, 0xdeadbeef, 3]);
+
+        assert_eq!(Reply::decode(&round_trip(Frame::new(Opcode::Reply, reply.encode()))).unwrap(), reply);
+    }
+
+    #[test]
+    fn requests_decode_from_their_discriminant() {
+        for request in Request::ALL {
+            assert_eq!(Request::from(request as u8), request);
+        }
+
+        assert_eq!(Request::from(Request::Unknown as u8), Request::Unknown);
+        assert_eq!(Request::from(u8::MAX), Request::Unknown);
+    }
+
+    #[test]
+    fn legacy_sequences_keep_their_requests() {
+        let legacy = |request: u8, value: u32| {
+            let mut bytes = vec![request];
+
+            bytes.extend(value.to_ne_bytes());
+
+            Sequence::decode_legacy(&bytes)
+        };
+
+        // discriminants of the packed sequences sent by older clients
+        for (byte, request) in [
+            (0, Request::Workspace),
+            (1, Request::Kill),
+            (7, Request::WindowGaps),
+            (13, Request::FocusMaster),
+            (25, Request::WorkspacePerMonitor),
+            (26, Request::MonitorCirculate),
+            (27, Request::Quit),
+        ] {
+            let sequence = legacy(byte, 0x1234);
+
+            assert_eq!(sequence.request, request);
+            assert_eq!(sequence.value(), 0x1234);
+        }
+    }
+
+    #[test]
+    fn handshake_rejects_other_versions() {
+        let (client, mut server) = UnixStream::pair().unwrap();
+
+        let mut hello = Frame::new(Opcode::Hello, Vec::new());
+
+        hello.header.version = VERSION + 1;
+        hello.write(&mut server).unwrap();
+        server.flush().unwrap();
+
+        let err = Stream::from(client).handshake().unwrap_err();
+
+        assert!(err.to_string().starts_with("protocol version mismatch"));
+
+        let (client, mut server) = UnixStream::pair().unwrap();
+
+        Frame::new(Opcode::Hello, Vec::new()).write(&mut server).unwrap();
+
+        assert!(Stream::from(client).handshake().is_ok());
+    }
+}
@@ -5,10 +5,14 @@ use std::env;
 use std::iter::{Peekable, Skip};
 
 #[derive(Clone, Copy)]
-pub enum Rule<T: Clone + Copy> {
+pub enum Rule<T: Clone + Copy + 'static> {
     Flag(T),
     Integer(T),
     Hex(T),
+    String(T),
+    Modifier(T),
+    Choice(&'static [(&'static str, T)]),
+    Mask(T, &'static [(&'static str, u32)]),
 }
 
 #[derive(Debug)]
@@ -16,16 +20,18 @@ pub enum Argument<T: std::fmt::Debug> {
     Flag { kind: T },
     Integer { kind: T, value: u32 },
     Hex { kind: T, value: u32 },
+    String { kind: T, value: String },
+    Modifier { kind: T, arg: String },
 }
 
-pub struct Args<T: Clone + Copy + std::fmt::Debug> {
+pub struct Args<T: Clone + Copy + std::fmt::Debug + 'static> {
     rules: HashMap<String, Rule<T>>,
     args: Peekable<Skip<env::Args>>,
 }
 
 impl<T> Args<T>
 where
-    T: Clone + Copy + std::fmt::Debug,
+    T: Clone + Copy + std::fmt::Debug + 'static,
 {
     pub fn new() -> Args<T> {
         Args {
@@ -42,8 +48,9 @@ where
         self.args.peek().is_none()
     }
 
-    fn parse_rule(&mut self, rule: Rule<T>) -> Result<Argument<T>, Box<dyn std::error::Error>> {
+    fn parse_rule(&mut self, arg: String, rule: Rule<T>) -> Result<Argument<T>, Box<dyn std::error::Error>> {
         match rule {
+            Rule::Modifier(kind) => Ok(Argument::Modifier { kind, arg }),
             Rule::Flag(kind) => Ok(Argument::Flag { kind }),
             Rule::Integer(kind) => Ok(Argument::Integer {
                 kind,
@@ -53,6 +60,36 @@ where
                 kind,
                 value: u32::from_str_radix(&self.parse_next()?, 16)?,
             }),
+            Rule::String(kind) => Ok(Argument::String {
+                kind,
+                value: self.parse_next()?,
+            }),
+            Rule::Choice(choices) => {
+                let arg = self.parse_next()?;
+
+                choices
+                    .iter()
+                    .find(|(name, _)| *name == arg)
+                    .map(|(_, kind)| Argument::Flag { kind: *kind })
+                    .ok_or(Error::Unknown { arg })
+                    .map_err(|err| err.into())
+            }
+            Rule::Mask(kind, bits) => {
+                let value = self
+                    .parse_next()?
+                    .split(',')
+                    .map(|arg| {
+                        bits.iter()
+                            .find(|(name, _)| *name == arg)
+                            .map(|(_, bit)| *bit)
+                            .ok_or(Error::Unknown {
+                                arg: arg.to_string(),
+                            })
+                    })
+                    .try_fold(0, |mask, bit| bit.map(|bit| mask | bit))?;
+
+                Ok(Argument::Integer { kind, value })
+            }
         }
     }
 
@@ -68,9 +105,9 @@ where
 
         rules
             .get(&arg)
-            .ok_or(Error::Unknown { arg })
+            .ok_or(Error::Unknown { arg: arg.clone() })
             .map_err(|err| err.into())
-            .and_then(|rule| self.parse_rule(*rule))
+            .and_then(|rule| self.parse_rule(arg, *rule))
     }
 
     pub fn next(&mut self) -> Result<Argument<T>, Box<dyn std::error::Error>> {
@@ -3,6 +3,7 @@ pub enum Error {
     Expected,
     ArgsEmpty,
     Unknown { arg: String },
+    Misplaced { arg: String },
 }
 
 impl std::fmt::Display for Error {
@@ -11,6 +12,9 @@ impl std::fmt::Display for Error {
             Error::Expected => f.write_str("expected more arguments"),
             Error::ArgsEmpty => f.write_str("no more arguments"),
             Error::Unknown { arg } => f.write_fmt(format_args!("unknown argument: {}", arg)),
+            Error::Misplaced { arg } => {
+                f.write_fmt(format_args!("{} does not follow the argument it modifies", arg))
+            }
         }
     }
 }
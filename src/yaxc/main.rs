@@ -2,13 +2,19 @@ mod args;
 mod error;
 
 use args::{Args, Argument, Rule};
+use error::Error;
 
-use proto::{Request, Sequence, Stream};
+use proto::{Notification, Opcode, Reply, Request, Sequence, Status, Stream, Topic, Value};
 
-const ARGUMENTS: [(&str, Rule<Request>); 28] = [
+use std::io::{self, Write};
+use std::process;
+
+const ARGUMENTS: [(&str, Rule<Request>); 56] = [
     ("--kill", Rule::Flag(Request::Kill)),
     ("--close", Rule::Flag(Request::Close)),
     ("--workspace", Rule::Integer(Request::Workspace)),
+    ("--send-to-workspace", Rule::Integer(Request::SendToWorkspace)),
+    ("--follow", Rule::Modifier(Request::SendToWorkspace)),
     ("--padding-top", Rule::Integer(Request::PaddingTop)),
     ("--padding-bottom", Rule::Integer(Request::PaddingBottom)),
     ("--padding-left", Rule::Integer(Request::PaddingLeft)),
@@ -20,7 +26,18 @@ const ARGUMENTS: [(&str, Rule<Request>); 28] = [
     ("--focus-up", Rule::Flag(Request::FocusUp)),
     ("--focus-down", Rule::Flag(Request::FocusDown)),
     ("--focus-master", Rule::Flag(Request::FocusMaster)),
+    ("--focus-dir", Rule::String(Request::FocusDir)),
+    ("--focus-float-toggle", Rule::Flag(Request::FocusFloatToggle)),
+    ("--focus-scope", Rule::String(Request::FocusScope)),
+    ("--focus-last", Rule::Flag(Request::FocusLast)),
+    ("--focus-cycle-mru", Rule::Flag(Request::FocusCycleMru)),
+    ("--swap-up", Rule::Flag(Request::SwapUp)),
+    ("--swap-down", Rule::Flag(Request::SwapDown)),
+    ("--swap-master", Rule::Flag(Request::SwapMaster)),
+    ("--rotate-stack", Rule::Flag(Request::RotateStack)),
+    ("--swap-dir", Rule::String(Request::SwapDir)),
     ("--float-toggle", Rule::Flag(Request::FloatToggle)),
+    ("--fullscreen-toggle", Rule::Flag(Request::FullscreenToggle)),
     ("--float-left", Rule::Integer(Request::FloatLeft)),
     ("--float-right", Rule::Integer(Request::FloatRight)),
     ("--float-up", Rule::Integer(Request::FloatUp)),
@@ -36,7 +53,38 @@ const ARGUMENTS: [(&str, Rule<Request>); 28] = [
         Rule::Integer(Request::WorkspacePerMonitor),
     ),
     ("--monitor-circulate", Rule::Flag(Request::MonitorCirculate)),
-    ("--quit", Rule::Flag(Request::Quit))
+    ("--quit", Rule::Flag(Request::Quit)),
+    ("--query", Rule::Choice(&QUERIES)),
+    ("--subscribe", Rule::Mask(Request::Subscribe, &TOPICS)),
+    ("--layout", Rule::String(Request::Layout)),
+    ("--layout-next", Rule::Flag(Request::LayoutNext)),
+    ("--master-ratio", Rule::Integer(Request::MasterRatio)),
+    ("--master-ratio-inc", Rule::Integer(Request::MasterRatioInc)),
+    ("--master-ratio-dec", Rule::Integer(Request::MasterRatioDec)),
+    ("--nmaster", Rule::Integer(Request::NMaster)),
+    ("--toggle-docks", Rule::Flag(Request::ToggleDocks)),
+    ("--reload", Rule::Flag(Request::Reload)),
+    ("--rule-add", Rule::String(Request::RuleAdd)),
+    ("--rule-remove", Rule::Integer(Request::RuleRemove)),
+    ("--rule-list", Rule::Flag(Request::RuleList)),
+    ("--scratchpad-add", Rule::String(Request::ScratchpadAdd)),
+    ("--scratchpad-toggle", Rule::String(Request::ScratchpadToggle)),
+];
+
+const QUERIES: [(&str, Request); 4] = [
+    ("focused", Request::QueryFocused),
+    ("workspaces", Request::QueryWorkspaces),
+    ("clients", Request::QueryClients),
+    ("config", Request::QueryConfig),
+];
+
+const TOPICS: [(&str, u32); 6] = [
+    ("focus", Topic::Focus as u32),
+    ("workspace", Topic::Workspace as u32),
+    ("monitor", Topic::Monitor as u32),
+    ("client", Topic::Client as u32),
+    ("config", Topic::Config as u32),
+    ("all", Topic::ALL),
 ];
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -46,17 +94,58 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         args.append(key, value);
     }
 
-    let mut stream = Stream::connect()?;
+    let mut sequences: Vec<Sequence> = Vec::new();
 
     while !args.is_empty() {
         match args.next()? {
             Argument::Flag { kind } => {
-                stream.send(Sequence::new(kind, 0))?;
+                sequences.push(Sequence::new(kind, 0));
             }
             Argument::Integer { kind, value } | Argument::Hex { kind, value } => {
-                stream.send(Sequence::new(kind, value))?;
+                sequences.push(Sequence::new(kind, value));
+            }
+            Argument::String { kind, value } => {
+                sequences.push(Sequence::with_values(kind, vec![Value::String(value)]));
             }
+            Argument::Modifier { kind, arg } => match sequences.last_mut() {
+                Some(sequence) if sequence.request == kind => {
+                    sequence.values.push(Value::Integer(1));
+                }
+                _ => return Err(Error::Misplaced { arg }.into()),
+            },
+        }
+    }
+
+    let mut stream = Stream::connect()?;
+
+    for sequence in sequences {
+        stream.send(sequence)?;
+    }
+
+    stream.finish()?;
+
+    let mut stdout = io::stdout();
+    let mut status = Status::Ok;
+
+    while let Some(frame) = stream.frame()? {
+        match frame.header.opcode {
+            Opcode::Reply => match Reply::decode(&frame)? {
+                Reply::Status(code, message) if code != Status::Ok => {
+                    eprintln!("yaxc: {}", message);
+
+                    status = code;
+                }
+                reply => write!(stdout, "{}", reply)?,
+            },
+            Opcode::Notification => writeln!(stdout, "{}", Notification::decode(&frame)?)?,
+            opcode => return Err(format!("unexpected frame: {:?}", opcode).into()),
         }
+
+        stdout.flush()?;
+    }
+
+    if status != Status::Ok {
+        process::exit(status as i32);
     }
 
     Ok(())
@@ -0,0 +1,329 @@
+use super::{Backend, Handle};
+use crate::wm::Area;
+
+use yaxi::display::Atom;
+use yaxi::ewmh::EwmhWindowType;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub const ROOT: u32 = 1;
+
+const FIRST_ATOM: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Call {
+    Map(u32),
+    Unmap(u32),
+    MovResize(u32, Area),
+    Raise(u32),
+    Lower(u32),
+    Focus(u32),
+    Kill(u32),
+    Close(u32),
+    WarpPointer(u16, u16),
+}
+
+#[derive(Default)]
+struct FakeWindow {
+    geometry: Area,
+    mapped: bool,
+    border_width: u16,
+    border_pixel: u32,
+    type_: Vec<EwmhWindowType>,
+    properties: HashMap<u32, Vec<u32>>,
+    texts: HashMap<u32, Vec<String>>,
+}
+
+#[derive(Default)]
+struct State {
+    windows: HashMap<u32, FakeWindow>,
+    atoms: Vec<String>,
+    screens: Vec<Area>,
+    pointer: (u16, u16),
+    focus: u32,
+    calls: Vec<Call>,
+}
+
+// an in-memory display that records every call the window manager makes
+#[derive(Clone)]
+pub struct Fake {
+    state: Rc<RefCell<State>>,
+}
+
+impl Fake {
+    pub fn new(screens: &[Area]) -> Fake {
+        let fake = Fake {
+            state: Rc::new(RefCell::new(State {
+                screens: screens.to_vec(),
+                ..State::default()
+            })),
+        };
+
+        fake.create(ROOT, Vec::new());
+
+        fake
+    }
+
+    pub fn create(&self, wid: u32, type_: Vec<EwmhWindowType>) -> Window {
+        self.state.borrow_mut().windows.insert(
+            wid,
+            FakeWindow {
+                geometry: Area::new(0, 0, 100, 100),
+                type_,
+                ..FakeWindow::default()
+            },
+        );
+
+        Window {
+            id: wid,
+            state: self.state.clone(),
+        }
+    }
+
+    pub fn set_pointer(&self, x: u16, y: u16) {
+        self.state.borrow_mut().pointer = (x, y);
+    }
+
+    pub fn set_focus(&self, wid: u32) {
+        self.state.borrow_mut().focus = wid;
+    }
+
+    pub fn geometry(&self, wid: u32) -> Area {
+        self.state.borrow().windows[&wid].geometry
+    }
+
+    pub fn is_mapped(&self, wid: u32) -> bool {
+        self.state.borrow().windows[&wid].mapped
+    }
+
+    pub fn border(&self, wid: u32) -> (u16, u32) {
+        let state = self.state.borrow();
+        let window = &state.windows[&wid];
+
+        (window.border_width, window.border_pixel)
+    }
+
+    pub fn property(&self, wid: u32, name: &str) -> Vec<u32> {
+        let state = self.state.borrow();
+
+        state
+            .atoms
+            .iter()
+            .position(|atom| atom == name)
+            .and_then(|index| state.windows[&wid].properties.get(&(FIRST_ATOM + index as u32)))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn set_text(&self, wid: u32, name: &str, text: &[&str]) {
+        let atom = self.intern_atom(name).unwrap();
+
+        if let Some(window) = self.state.borrow_mut().windows.get_mut(&wid) {
+            window.texts.insert(atom.id(), text.iter().map(|text| text.to_string()).collect());
+        }
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.state.borrow_mut().calls.drain(..).collect()
+    }
+}
+
+#[derive(Clone)]
+pub struct Window {
+    id: u32,
+    state: Rc<RefCell<State>>,
+}
+
+impl Window {
+    fn record(&self, call: Call) {
+        self.state.borrow_mut().calls.push(call);
+    }
+
+    fn with<F>(&self, f: F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&mut FakeWindow),
+    {
+        let mut state = self.state.borrow_mut();
+
+        let window = state
+            .windows
+            .get_mut(&self.id)
+            .ok_or_else(|| format!("bad window: {}", self.id))?;
+
+        f(window);
+
+        Ok(())
+    }
+}
+
+impl Handle for Window {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn map(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.record(Call::Map(self.id));
+
+        self.with(|window| window.mapped = true)
+    }
+
+    fn unmap(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.record(Call::Unmap(self.id));
+
+        self.with(|window| window.mapped = false)
+    }
+
+    fn mov_resize(&self, x: u16, y: u16, width: u16, height: u16) -> Result<(), Box<dyn std::error::Error>> {
+        let area = Area::new(x, y, width, height);
+
+        self.record(Call::MovResize(self.id, area));
+
+        self.with(|window| window.geometry = area)
+    }
+
+    fn geometry(&self) -> Result<Area, Box<dyn std::error::Error>> {
+        let mut geometry = Area::default();
+
+        self.with(|window| geometry = window.geometry)?;
+
+        Ok(geometry)
+    }
+
+    fn raise(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.record(Call::Raise(self.id));
+
+        Ok(())
+    }
+
+    fn lower(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.record(Call::Lower(self.id));
+
+        Ok(())
+    }
+
+    fn set_border_width(&self, width: u16) -> Result<(), Box<dyn std::error::Error>> {
+        self.with(|window| window.border_width = width)
+    }
+
+    fn set_border_pixel(&self, pixel: u32) -> Result<(), Box<dyn std::error::Error>> {
+        self.with(|window| window.border_pixel = pixel)
+    }
+
+    fn focus(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.record(Call::Focus(self.id));
+
+        self.state.borrow_mut().focus = self.id;
+
+        Ok(())
+    }
+
+    fn kill(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.record(Call::Kill(self.id));
+
+        Ok(())
+    }
+
+    fn close(&self, _protocols: Atom, _delete: Atom) -> Result<(), Box<dyn std::error::Error>> {
+        self.record(Call::Close(self.id));
+
+        Ok(())
+    }
+
+    fn property(&self, property: Atom, _type: Atom) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+        let mut data = Vec::new();
+
+        self.with(|window| {
+            data = window
+                .properties
+                .get(&property.id())
+                .cloned()
+                .unwrap_or_default()
+        })?;
+
+        Ok(data)
+    }
+
+    fn set_property(&self, property: Atom, _type: Atom, data: &[u32]) -> Result<(), Box<dyn std::error::Error>> {
+        self.with(|window| {
+            window.properties.insert(property.id(), data.to_vec());
+        })
+    }
+
+    fn text(&self, property: Atom, _type: Atom) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut text = Vec::new();
+
+        self.with(|window| text = window.texts.get(&property.id()).cloned().unwrap_or_default())?;
+
+        Ok(text)
+    }
+}
+
+impl Backend for Fake {
+    type Window = Window;
+
+    fn root(&self) -> Window {
+        Window {
+            id: ROOT,
+            state: self.state.clone(),
+        }
+    }
+
+    fn window(&self, wid: u32) -> Result<Window, Box<dyn std::error::Error>> {
+        if !self.state.borrow().windows.contains_key(&wid) {
+            return Err(format!("bad window: {}", wid).into());
+        }
+
+        Ok(Window {
+            id: wid,
+            state: self.state.clone(),
+        })
+    }
+
+    fn input_focus(&self) -> Result<u32, Box<dyn std::error::Error>> {
+        Ok(self.state.borrow().focus)
+    }
+
+    fn query_pointer(&self) -> Result<(u16, u16), Box<dyn std::error::Error>> {
+        Ok(self.state.borrow().pointer)
+    }
+
+    fn warp_pointer(&self, x: u16, y: u16) -> Result<(), Box<dyn std::error::Error>> {
+        let mut state = self.state.borrow_mut();
+
+        state.calls.push(Call::WarpPointer(x, y));
+        state.pointer = (x, y);
+
+        Ok(())
+    }
+
+    fn screens(&self) -> Result<Vec<Area>, Box<dyn std::error::Error>> {
+        Ok(self.state.borrow().screens.clone())
+    }
+
+    fn window_type(&self, window: &Window) -> Result<Vec<EwmhWindowType>, Box<dyn std::error::Error>> {
+        Ok(self
+            .state
+            .borrow()
+            .windows
+            .get(&window.id)
+            .map(|window| window.type_.clone())
+            .unwrap_or_default())
+    }
+
+    fn intern_atom(&self, name: &str) -> Result<Atom, Box<dyn std::error::Error>> {
+        let mut state = self.state.borrow_mut();
+
+        let index = match state.atoms.iter().position(|atom| atom == name) {
+            Some(index) => index,
+            None => {
+                state.atoms.push(name.to_string());
+
+                state.atoms.len() - 1
+            }
+        };
+
+        Ok(Atom::new(FIRST_ATOM + index as u32))
+    }
+}
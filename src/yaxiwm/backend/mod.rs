@@ -0,0 +1,206 @@
+#[cfg(test)]
+pub mod fake;
+
+use crate::wm::Area;
+
+use yaxi::display::{self, Atom, Display};
+use yaxi::ewmh::EwmhWindowType;
+use yaxi::proto::{ClientMessageData, Event, PropFormat, PropMode, RevertTo};
+use yaxi::window::{Window, WindowKind};
+
+
+pub trait Handle: Clone {
+    fn id(&self) -> u32;
+
+    fn map(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn unmap(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn mov_resize(&self, x: u16, y: u16, width: u16, height: u16) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn geometry(&self) -> Result<Area, Box<dyn std::error::Error>>;
+
+    fn raise(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn lower(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn set_border_width(&self, width: u16) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn set_border_pixel(&self, pixel: u32) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn focus(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn kill(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn close(&self, protocols: Atom, delete: Atom) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn property(&self, property: Atom, type_: Atom) -> Result<Vec<u32>, Box<dyn std::error::Error>>;
+
+    fn set_property(&self, property: Atom, type_: Atom, data: &[u32]) -> Result<(), Box<dyn std::error::Error>>;
+
+    // string properties hold one or more null terminated strings
+    fn text(&self, property: Atom, type_: Atom) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+}
+
+pub trait Backend: Clone {
+    type Window: Handle;
+
+    fn root(&self) -> Self::Window;
+
+    fn window(&self, wid: u32) -> Result<Self::Window, Box<dyn std::error::Error>>;
+
+    fn input_focus(&self) -> Result<u32, Box<dyn std::error::Error>>;
+
+    fn query_pointer(&self) -> Result<(u16, u16), Box<dyn std::error::Error>>;
+
+    fn warp_pointer(&self, x: u16, y: u16) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn screens(&self) -> Result<Vec<Area>, Box<dyn std::error::Error>>;
+
+    fn window_type(&self, window: &Self::Window) -> Result<Vec<EwmhWindowType>, Box<dyn std::error::Error>>;
+
+    fn intern_atom(&self, name: &str) -> Result<Atom, Box<dyn std::error::Error>>;
+}
+
+impl Handle for Window {
+    fn id(&self) -> u32 {
+        Window::id(self)
+    }
+
+    fn map(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Window::map(self, WindowKind::Window)
+    }
+
+    fn unmap(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Window::unmap(self, WindowKind::Window)
+    }
+
+    fn mov_resize(&self, x: u16, y: u16, width: u16, height: u16) -> Result<(), Box<dyn std::error::Error>> {
+        Window::mov_resize(self, x, y, width, height)
+    }
+
+    fn geometry(&self) -> Result<Area, Box<dyn std::error::Error>> {
+        let geometry = self.get_geometry()?;
+
+        Ok(Area::new(geometry.x, geometry.y, geometry.width, geometry.height))
+    }
+
+    fn raise(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Window::raise(self)
+    }
+
+    fn lower(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Window::lower(self)
+    }
+
+    fn set_border_width(&self, width: u16) -> Result<(), Box<dyn std::error::Error>> {
+        Window::set_border_width(self, width)
+    }
+
+    fn set_border_pixel(&self, pixel: u32) -> Result<(), Box<dyn std::error::Error>> {
+        Window::set_border_pixel(self, pixel)
+    }
+
+    fn focus(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.set_input_focus(RevertTo::Parent)
+    }
+
+    fn kill(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Window::kill(self)
+    }
+
+    fn close(&self, protocols: Atom, delete: Atom) -> Result<(), Box<dyn std::error::Error>> {
+        self.send_event(
+            Event::ClientMessage {
+                format: 32,
+                window: Window::id(self),
+                type_: protocols,
+                data: ClientMessageData::Long([delete.id(), 0, 0, 0, 0]),
+            },
+            vec![],
+            false,
+        )
+    }
+
+    fn property(&self, property: Atom, type_: Atom) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+        Ok(self
+            .get_property(property, type_, false)?
+            .chunks_exact(4)
+            .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect())
+    }
+
+    fn set_property(&self, property: Atom, type_: Atom, data: &[u32]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut data = data.to_vec();
+
+        self.change_property(property, type_, PropFormat::Format32, PropMode::Replace, &mut data)
+    }
+
+    fn text(&self, property: Atom, type_: Atom) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Ok(self
+            .get_property(property, type_, false)?
+            .split(|byte| *byte == 0)
+            .filter(|text| !text.is_empty())
+            .map(|text| String::from_utf8_lossy(text).into_owned())
+            .collect())
+    }
+}
+
+#[derive(Clone)]
+pub struct X11 {
+    pub display: Display,
+    root: Window,
+}
+
+impl X11 {
+    pub fn open() -> Result<X11, Box<dyn std::error::Error>> {
+        let display = display::open(None)?;
+        let root = display.default_root_window()?;
+
+        Ok(X11 { display, root })
+    }
+}
+
+impl Backend for X11 {
+    type Window = Window;
+
+    fn root(&self) -> Window {
+        self.root.clone()
+    }
+
+    fn window(&self, wid: u32) -> Result<Window, Box<dyn std::error::Error>> {
+        self.display.window_from_id(wid)
+    }
+
+    fn input_focus(&self) -> Result<u32, Box<dyn std::error::Error>> {
+        Ok(self.display.get_input_focus()?.window)
+    }
+
+    fn query_pointer(&self) -> Result<(u16, u16), Box<dyn std::error::Error>> {
+        let pointer = self.root.query_pointer()?;
+
+        Ok((pointer.root_x, pointer.root_y))
+    }
+
+    fn warp_pointer(&self, x: u16, y: u16) -> Result<(), Box<dyn std::error::Error>> {
+        self.root.warp_pointer(x, y)
+    }
+
+    fn screens(&self) -> Result<Vec<Area>, Box<dyn std::error::Error>> {
+        Ok(self
+            .display
+            .query_xinerama()?
+            .query_screens()?
+            .iter()
+            .map(|screen| Area::new(screen.x, screen.y, screen.width, screen.height))
+            .collect())
+    }
+
+    fn window_type(&self, window: &Window) -> Result<Vec<EwmhWindowType>, Box<dyn std::error::Error>> {
+        self.display.use_ewmh(window).get_wm_window_type()
+    }
+
+    fn intern_atom(&self, name: &str) -> Result<Atom, Box<dyn std::error::Error>> {
+        self.display.intern_atom(name, false)
+    }
+}
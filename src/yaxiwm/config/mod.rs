@@ -1,3 +1,14 @@
+mod parser;
+
+use crate::layout::Layout;
+use crate::rules::Rule;
+
+use parser::{Table, Value};
+
+use std::env;
+use std::fs;
+use std::io;
+
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Padding {
     pub top: u16,
@@ -13,15 +24,285 @@ pub struct Borders {
     pub normal: u32,
 }
 
+// which clients focus-up and focus-down cycle through
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FocusScope {
+    #[default]
+    All,
+    Tiled,
+    Floating,
+}
+
+impl FocusScope {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FocusScope::All => "all",
+            FocusScope::Tiled => "tiled",
+            FocusScope::Floating => "floating",
+        }
+    }
+}
+
+impl TryFrom<&str> for FocusScope {
+    type Error = String;
+
+    fn try_from(name: &str) -> Result<FocusScope, String> {
+        [FocusScope::All, FocusScope::Tiled, FocusScope::Floating]
+            .into_iter()
+            .find(|scope| scope.name() == name)
+            .ok_or_else(|| format!("unknown focus scope: {}", name))
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Windows {
     pub borders: Borders,
     pub gaps: u16,
     pub mouse_movement: bool,
+    pub size_hints: bool,
+    pub focus_scope: FocusScope,
+}
+
+// unset values leave the workspaces as they are
+#[derive(Debug, Default)]
+pub struct Workspaces {
+    pub per_monitor: Option<u32>,
+    pub layout: Option<Layout>,
+    pub layouts: Vec<Layout>,
+}
+
+impl Workspaces {
+    pub fn layout(&self, index: usize) -> Option<Layout> {
+        self.layouts.get(index).copied().or(self.layout)
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct Config {
     pub padding: Padding,
     pub windows: Windows,
+    pub workspaces: Workspaces,
+    pub rules: Vec<Rule>,
+    pub scratchpads: Vec<(String, String)>,
+}
+
+impl Config {
+    pub fn load() -> Result<Config, String> {
+        let path = format!("{}/.config/yaxiwm/yaxiwm.conf", env::var("HOME").map_err(|err| err.to_string())?);
+
+        match fs::read_to_string(&path) {
+            Ok(source) => Config::parse(&source).map_err(|err| format!("{}: {}", path, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(format!("{}: {}", path, err)),
+        }
+    }
+
+    pub fn parse(source: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+
+        for table in parser::parse(source)? {
+            if table.name == "rule" {
+                config.rules.push(Config::rule(&table)?);
+
+                continue;
+            }
+
+            for entry in &table.entries {
+                config
+                    .set(&table.name, &entry.key, &entry.value)
+                    .map_err(|err| format!("line {}: {}", entry.line, err))?;
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn rule(table: &Table) -> Result<Rule, String> {
+        let mut rule = Rule::default();
+
+        for entry in &table.entries {
+            rule.set(&entry.key, &entry.value.to_string())
+                .map_err(|err| format!("line {}: {}", entry.line, err))?;
+        }
+
+        rule.validate().map_err(|err| format!("line {}: {}", table.line, err))?;
+
+        Ok(rule)
+    }
+
+    fn set(&mut self, table: &str, key: &str, value: &Value) -> Result<(), String> {
+        match (table, key) {
+            ("padding", "top") => self.padding.top = small(value)?,
+            ("padding", "bottom") => self.padding.bottom = small(value)?,
+            ("padding", "left") => self.padding.left = small(value)?,
+            ("padding", "right") => self.padding.right = small(value)?,
+            ("windows", "gaps") => self.windows.gaps = small(value)?,
+            ("windows", "mouse-movement") => self.windows.mouse_movement = value.bool()?,
+            ("windows", "size-hints") => self.windows.size_hints = value.bool()?,
+            ("windows", "focus-scope") => {
+                self.windows.focus_scope = FocusScope::try_from(value.string()?)?;
+            }
+            ("borders", "width") => self.windows.borders.width = small(value)?,
+            ("borders", "focused") => self.windows.borders.focused = value.integer()?,
+            ("borders", "normal") => self.windows.borders.normal = value.integer()?,
+            ("workspaces", "per-monitor") => {
+                self.workspaces.per_monitor = match value.integer()? {
+                    0 => return Err(String::from("workspaces per monitor must be at least 1")),
+                    count => Some(count),
+                };
+            }
+            ("workspaces", "layout") => {
+                self.workspaces.layout = Some(Layout::try_from(value.string()?)?);
+            }
+            ("workspaces", "layouts") => {
+                self.workspaces.layouts = value
+                    .array()?
+                    .iter()
+                    .map(|value| Layout::try_from(value.string()?))
+                    .collect::<Result<Vec<Layout>, String>>()?;
+            }
+            ("scratchpads", name) => {
+                self.scratchpads.retain(|(other, _)| other != name);
+                self.scratchpads.push((name.to_string(), value.string()?.to_string()));
+            }
+            ("", key) => return Err(format!("{} is outside of a table", key)),
+            (table, key) => return Err(format!("unknown setting: {}.{}", table, key)),
+        }
+
+        Ok(())
+    }
+
+    pub fn entries(&self) -> Vec<(String, u32)> {
+        vec![
+            ("padding-top", self.padding.top as u32),
+            ("padding-bottom", self.padding.bottom as u32),
+            ("padding-left", self.padding.left as u32),
+            ("padding-right", self.padding.right as u32),
+            ("window-gaps", self.windows.gaps as u32),
+            ("border-width", self.windows.borders.width as u32),
+            ("focused-border", self.windows.borders.focused),
+            ("normal-border", self.windows.borders.normal),
+            ("mouse-movement", self.windows.mouse_movement as u32),
+            ("size-hints", self.windows.size_hints as u32),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+    }
+}
+
+fn small(value: &Value) -> Result<u16, String> {
+    let value = value.integer()?;
+
+    u16::try_from(value).map_err(|_| format!("{} is too large", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_table() {
+        let config = Config::parse(
+            r#"
+            # comments and blank lines are ignored
+            [padding]
+            top = 30
+
+            [windows]
+            gaps = 8
+            mouse-movement = true
+            focus-scope = "tiled"
+
+            [borders]
+            width = 2
+            focused = 0xff5577 # trailing comments too
+            normal = 0x333333
+
+            [workspaces]
+            per-monitor = 5
+            layout = "tile"
+            layouts = ["monocle", "grid"]
+
+            [scratchpads]
+            term = "st -c dropdown"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.padding.top, 30);
+        assert_eq!(config.windows.gaps, 8);
+        assert!(config.windows.mouse_movement);
+        assert_eq!(config.windows.focus_scope, FocusScope::Tiled);
+        assert_eq!(config.windows.borders.width, 2);
+        assert_eq!(config.windows.borders.focused, 0xff5577);
+        assert_eq!(config.windows.borders.normal, 0x333333);
+        assert_eq!(config.workspaces.per_monitor, Some(5));
+        assert_eq!(config.workspaces.layout(0), Some(Layout::Monocle));
+        assert_eq!(config.workspaces.layout(1), Some(Layout::Grid));
+        assert_eq!(config.workspaces.layout(4), Some(Layout::Tile));
+        assert_eq!(config.scratchpads, vec![(String::from("term"), String::from("st -c dropdown"))]);
+    }
+
+    #[test]
+    fn parses_rules() {
+        let config = Config::parse(
+            r#"
+            [[rule]]
+            class = "Firefox"
+            type = "dialog"
+            state = "float"
+            geometry = [0, 0, 800, 600]
+
+            [[rule]]
+            name = "scratch"
+            workspace = 3
+            focus = false
+            "#,
+        )
+        .unwrap();
+
+        let rules = config.rules.iter().map(|rule| rule.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(
+            rules,
+            vec![
+                "class=Firefox type=dialog state=float geometry=0,0,800,600",
+                "name=scratch workspace=3 focus=false",
+            ]
+        );
+
+        assert_eq!(
+            Config::parse("[[rule]]\nstate = \"float\"").unwrap_err(),
+            "line 1: rule must match on class, instance, name, role or type"
+        );
+    }
+
+    #[test]
+    fn empty_config_is_default() {
+        let config = Config::parse("").unwrap();
+
+        assert_eq!(config.entries(), Config::default().entries());
+        assert_eq!(config.workspaces.per_monitor, None);
+        assert_eq!(config.workspaces.layout(0), None);
+    }
+
+    #[test]
+    fn errors_point_at_the_line() {
+        let errors = [
+            ("[padding]\ntop = 70000", "line 2: 70000 is too large"),
+            ("[windows]\n\ngaps = true", "line 3: expected an integer"),
+            ("[padding]\nmiddle = 1", "line 2: unknown setting: padding.middle"),
+            ("gaps = 1", "line 1: gaps is outside of a table"),
+            ("[workspaces]\nlayout = \"stack\"", "line 2: unknown layout: stack"),
+            ("[windows]\nfocus-scope = \"docks\"", "line 2: unknown focus scope: docks"),
+            ("[borders]\nwidth 2", "line 2: expected key = value"),
+            ("[borders]\n[borders]", "line 2: table borders is defined twice"),
+            ("[workspaces]\nlayouts = [\"tile\"", "line 2: unterminated array"),
+        ];
+
+        for (source, error) in errors {
+            assert_eq!(Config::parse(source).unwrap_err(), error);
+        }
+    }
 }
@@ -0,0 +1,203 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(u32),
+    Bool(bool),
+    String(String),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn integer(&self) -> Result<u32, String> {
+        match self {
+            Value::Integer(value) => Ok(*value),
+            _ => Err(String::from("expected an integer")),
+        }
+    }
+
+    pub fn bool(&self) -> Result<bool, String> {
+        match self {
+            Value::Bool(value) => Ok(*value),
+            _ => Err(String::from("expected true or false")),
+        }
+    }
+
+    pub fn string(&self) -> Result<&str, String> {
+        match self {
+            Value::String(value) => Ok(value),
+            _ => Err(String::from("expected a string")),
+        }
+    }
+
+    pub fn array(&self) -> Result<&[Value], String> {
+        match self {
+            Value::Array(values) => Ok(values),
+            _ => Err(String::from("expected an array")),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::String(value) => f.write_str(value),
+            Value::Array(values) => {
+                let values = values.iter().map(|value| value.to_string()).collect::<Vec<String>>();
+
+                f.write_str(&values.join(","))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub value: Value,
+    pub line: usize,
+}
+
+// `[name]` starts a table, `[[name]]` starts another table of the same name, e.g. one per rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub name: String,
+    pub repeated: bool,
+    pub line: usize,
+    pub entries: Vec<Entry>,
+}
+
+pub fn parse(source: &str) -> Result<Vec<Table>, String> {
+    let mut tables = vec![Table {
+        name: String::new(),
+        repeated: false,
+        line: 0,
+        entries: Vec::new(),
+    }];
+
+    for (index, line) in source.lines().enumerate() {
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        parse_line(line, index + 1, &mut tables).map_err(|err| format!("line {}: {}", index + 1, err))?;
+    }
+
+    Ok(tables)
+}
+
+fn parse_line(line: &str, number: usize, tables: &mut Vec<Table>) -> Result<(), String> {
+    if let Some(name) = line.strip_prefix("[[").and_then(|line| line.strip_suffix("]]")) {
+        tables.push(Table {
+            name: name.trim().to_string(),
+            repeated: true,
+            line: number,
+            entries: Vec::new(),
+        });
+    } else if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+        let name = name.trim();
+
+        if tables.iter().any(|table| table.name == name && !table.repeated) {
+            return Err(format!("table {} is defined twice", name));
+        }
+
+        tables.push(Table {
+            name: name.to_string(),
+            repeated: false,
+            line: number,
+            entries: Vec::new(),
+        });
+    } else {
+        let (key, value) = line.split_once('=').ok_or("expected key = value")?;
+
+        let key = key.trim();
+
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("invalid key: {}", key));
+        }
+
+        let (value, rest) = parse_value(value.trim())?;
+
+        if !rest.trim().is_empty() {
+            return Err(format!("unexpected trailing input: {}", rest.trim()));
+        }
+
+        if let Some(table) = tables.last_mut() {
+            table.entries.push(Entry {
+                key: key.to_string(),
+                value,
+                line: number,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_value(input: &str) -> Result<(Value, &str), String> {
+    if let Some(rest) = input.strip_prefix('"') {
+        let end = rest.find('"').ok_or("unterminated string")?;
+
+        Ok((Value::String(rest[..end].to_string()), &rest[end + 1..]))
+    } else if let Some(mut rest) = input.strip_prefix('[') {
+        let mut values = Vec::new();
+
+        loop {
+            rest = rest.trim_start();
+
+            if let Some(rest) = rest.strip_prefix(']') {
+                return Ok((Value::Array(values), rest));
+            }
+
+            let (value, remaining) = parse_value(rest)?;
+
+            values.push(value);
+
+            rest = remaining.trim_start();
+            rest = rest.strip_prefix(',').unwrap_or(rest);
+
+            if rest.is_empty() {
+                return Err(String::from("unterminated array"));
+            }
+        }
+    } else {
+        let end = input
+            .find(|c: char| c == ',' || c == ']' || c.is_whitespace())
+            .unwrap_or(input.len());
+
+        let (word, rest) = input.split_at(end);
+
+        let value = match word {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::Integer(parse_integer(word)?),
+        };
+
+        Ok((value, rest))
+    }
+}
+
+fn parse_integer(word: &str) -> Result<u32, String> {
+    let parsed = match word.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => word.parse::<u32>(),
+    };
+
+    parsed.map_err(|_| format!("invalid value: {}", word))
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
@@ -1,9 +1,10 @@
 use yaxi::proto::Event;
 
-use proto::Sequence;
+use proto::{Sequence, Stream};
 
 use std::sync::{Arc, Mutex, Condvar};
 use std::collections::VecDeque;
+use std::mem;
 
 macro_rules! lock {
     ($mutex:expr) => {
@@ -13,7 +14,7 @@ macro_rules! lock {
 
 pub enum EventType {
     XEvent(Event),
-    Config(Sequence),
+    Config(Sequence, Stream),
 }
 
 #[derive(Clone)]
@@ -31,7 +32,16 @@ impl EventQueue {
     }
 
     pub fn push(&self, event: EventType) -> Result<(), Box<dyn std::error::Error>> {
-        lock!(self.events)?.push_back(event);
+        let mut events = lock!(self.events)?;
+
+        match (events.back_mut(), event) {
+            (Some(EventType::XEvent(pending)), EventType::XEvent(event)) => {
+                if let Some(event) = coalesce(pending, event) {
+                    events.push_back(EventType::XEvent(event));
+                }
+            }
+            (_, event) => events.push_back(event),
+        }
 
         self.cond.notify_all();
 
@@ -50,7 +60,7 @@ impl EventQueue {
         let mut guard = lock!(self.events)?;
 
         loop {
-            if let Some(event) = guard.pop_back() {
+            if let Some(event) = guard.pop_front() {
                 return Ok(event);
             } else {
                 guard = self.cond.wait(guard).map_err(|_| Into::<Box<dyn std::error::Error>>::into("failed to lock"))?;
@@ -58,3 +68,121 @@ impl EventQueue {
         }
     }
 }
+
+// a newer motion or configure request for the same window supersedes the pending one
+fn coalesce(pending: &mut Event, event: Event) -> Option<Event> {
+    match (pending, event) {
+        (
+            pending @ Event::MotionNotify { .. },
+            event @ Event::MotionNotify { .. },
+        ) if motion_window(pending) == motion_window(&event) => {
+            *pending = event;
+
+            None
+        }
+        (
+            Event::ConfigureRequest { window: pending, values },
+            Event::ConfigureRequest { window, values: newer },
+        ) if *pending == window => {
+            values.retain(|value| {
+                !newer
+                    .iter()
+                    .any(|newer| mem::discriminant(value) == mem::discriminant(newer))
+            });
+
+            values.extend(newer);
+
+            None
+        }
+        (_, event) => Some(event),
+    }
+}
+
+fn motion_window(event: &Event) -> Option<u32> {
+    match event {
+        Event::MotionNotify { window, .. } => Some(*window),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use yaxi::proto::{Coordinates, WindowValue};
+
+    fn motion(window: u32, x: u16) -> EventType {
+        EventType::XEvent(Event::MotionNotify {
+            coordinates: Coordinates { x, y: 0, root_x: x, root_y: 0 },
+            window,
+            root: 1,
+            subwindow: 0,
+            state: 0,
+            send_event: false,
+        })
+    }
+
+    fn next(queue: &EventQueue) -> Event {
+        match queue.wait().unwrap() {
+            EventType::XEvent(event) => event,
+            EventType::Config(..) => panic!("expected an x event"),
+        }
+    }
+
+    fn pending(queue: &EventQueue) -> usize {
+        queue.events.lock().unwrap().len()
+    }
+
+    #[test]
+    fn events_are_handled_oldest_first() {
+        let queue = EventQueue::new();
+
+        for window in [10, 11, 12] {
+            queue.push(EventType::XEvent(Event::MapRequest { parent: 1, window })).unwrap();
+        }
+
+        for expected in [10, 11, 12] {
+            assert!(matches!(next(&queue), Event::MapRequest { window, .. } if window == expected));
+        }
+    }
+
+    #[test]
+    fn motion_collapses_per_window() {
+        let queue = EventQueue::new();
+
+        queue.push(motion(10, 1)).unwrap();
+        queue.push(motion(10, 2)).unwrap();
+        queue.push(motion(11, 3)).unwrap();
+
+        assert_eq!(pending(&queue), 2);
+
+        assert!(matches!(next(&queue), Event::MotionNotify { window: 10, coordinates, .. } if coordinates.x == 2));
+        assert!(matches!(next(&queue), Event::MotionNotify { window: 11, coordinates, .. } if coordinates.x == 3));
+    }
+
+    #[test]
+    fn configure_requests_merge_with_newer_values_winning() {
+        let queue = EventQueue::new();
+
+        let configure = |window: u32, values: Vec<WindowValue>| {
+            EventType::XEvent(Event::ConfigureRequest { window, values })
+        };
+
+        queue.push(configure(10, vec![WindowValue::X(1), WindowValue::Width(100)])).unwrap();
+        queue.push(configure(10, vec![WindowValue::X(5), WindowValue::Height(50)])).unwrap();
+        queue.push(configure(11, vec![WindowValue::Y(7)])).unwrap();
+
+        assert_eq!(pending(&queue), 2);
+
+        let Event::ConfigureRequest { window: 10, values } = next(&queue) else {
+            panic!("expected a configure request for 10");
+        };
+
+        assert!(matches!(
+            values[..],
+            [WindowValue::Width(100), WindowValue::X(5), WindowValue::Height(50)]
+        ));
+
+        assert!(matches!(next(&queue), Event::ConfigureRequest { window: 11, .. }));
+    }
+}
@@ -0,0 +1,209 @@
+use crate::wm::Area;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Master {
+    pub ratio: u32,
+    pub count: usize,
+}
+
+impl Default for Master {
+    fn default() -> Master {
+        Master {
+            ratio: 50,
+            count: 1,
+        }
+    }
+}
+
+impl Master {
+    pub const MIN_RATIO: u32 = 5;
+    pub const MAX_RATIO: u32 = 95;
+
+    pub fn with_ratio(&self, ratio: u32) -> Master {
+        Master {
+            ratio: ratio.clamp(Master::MIN_RATIO, Master::MAX_RATIO),
+            count: self.count,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Tile,
+    Monocle,
+    Grid,
+    Columns,
+    Rows,
+    #[default]
+    Dwindle,
+    Spiral,
+}
+
+impl TryFrom<&str> for Layout {
+    type Error = String;
+
+    fn try_from(name: &str) -> Result<Layout, String> {
+        Layout::ALL
+            .into_iter()
+            .find(|layout| layout.name() == name)
+            .ok_or_else(|| format!("unknown layout: {}", name))
+    }
+}
+
+impl Layout {
+    pub const ALL: [Layout; 7] = [
+        Layout::Tile,
+        Layout::Monocle,
+        Layout::Grid,
+        Layout::Columns,
+        Layout::Rows,
+        Layout::Dwindle,
+        Layout::Spiral,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layout::Tile => "tile",
+            Layout::Monocle => "monocle",
+            Layout::Grid => "grid",
+            Layout::Columns => "columns",
+            Layout::Rows => "rows",
+            Layout::Dwindle => "dwindle",
+            Layout::Spiral => "spiral",
+        }
+    }
+
+    pub fn next(&self) -> Layout {
+        let index = Layout::ALL
+            .iter()
+            .position(|layout| layout == self)
+            .unwrap_or_default();
+
+        Layout::ALL[(index + 1) % Layout::ALL.len()]
+    }
+
+    pub fn arrange(&self, area: Area, count: usize, master: Master) -> Vec<Area> {
+        match count {
+            0 => Vec::new(),
+            1 => vec![area],
+            _ => match self {
+                Layout::Tile => tile(area, count, master),
+                Layout::Monocle => vec![area; count],
+                Layout::Grid => grid(area, count),
+                Layout::Columns => columns(area, count),
+                Layout::Rows => rows(area, count),
+                Layout::Dwindle => dwindle(area, count),
+                Layout::Spiral => spiral(area, count),
+            },
+        }
+    }
+}
+
+// the nth of `count` equal slices of `length` starting at `start`, the last slice absorbs rounding
+fn slice(start: u16, length: u16, index: usize, count: usize) -> (u16, u16) {
+    let begin = length as usize * index / count;
+    let end = length as usize * (index + 1) / count;
+
+    (start + begin as u16, (end - begin) as u16)
+}
+
+fn columns(area: Area, count: usize) -> Vec<Area> {
+    (0..count)
+        .map(|index| {
+            let (x, width) = slice(area.x, area.width, index, count);
+
+            Area::new(x, area.y, width, area.height)
+        })
+        .collect()
+}
+
+fn rows(area: Area, count: usize) -> Vec<Area> {
+    (0..count)
+        .map(|index| {
+            let (y, height) = slice(area.y, area.height, index, count);
+
+            Area::new(area.x, y, area.width, height)
+        })
+        .collect()
+}
+
+fn tile(area: Area, count: usize, master: Master) -> Vec<Area> {
+    if master.count == 0 || master.count >= count {
+        return rows(area, count);
+    }
+
+    let width = (area.width as u32 * master.ratio / 100) as u16;
+
+    let mut areas = rows(Area::new(area.x, area.y, width, area.height), master.count);
+
+    areas.extend(rows(
+        Area::new(area.x + width, area.y, area.width - width, area.height),
+        count - master.count,
+    ));
+
+    areas
+}
+
+fn grid(area: Area, count: usize) -> Vec<Area> {
+    let cols = (1..=count).find(|cols| cols * cols >= count).unwrap_or(count);
+    let lines = count.div_ceil(cols);
+
+    (0..count)
+        .map(|index| {
+            let line = index / cols;
+            let in_line = (count - line * cols).min(cols);
+
+            let (x, width) = slice(area.x, area.width, index % cols, in_line);
+            let (y, height) = slice(area.y, area.height, line, lines);
+
+            Area::new(x, y, width, height)
+        })
+        .collect()
+}
+
+fn dwindle(mut area: Area, count: usize) -> Vec<Area> {
+    let mut areas = (1..count).map(|_| area.split()).collect::<Vec<Area>>();
+
+    areas.push(area);
+
+    areas
+}
+
+fn spiral(mut area: Area, count: usize) -> Vec<Area> {
+    let mut areas = Vec::with_capacity(count);
+
+    for index in 0..count - 1 {
+        let vertical = index % 2 == 0;
+
+        let (first, second) = if vertical {
+            let (x, width) = slice(area.x, area.width, 0, 2);
+            let (rest_x, rest_width) = slice(area.x, area.width, 1, 2);
+
+            (
+                Area::new(x, area.y, width, area.height),
+                Area::new(rest_x, area.y, rest_width, area.height),
+            )
+        } else {
+            let (y, height) = slice(area.y, area.height, 0, 2);
+            let (rest_y, rest_height) = slice(area.y, area.height, 1, 2);
+
+            (
+                Area::new(area.x, y, area.width, height),
+                Area::new(area.x, rest_y, area.width, rest_height),
+            )
+        };
+
+        // every second turn the window takes the far half so the remaining space winds inwards
+        if index % 4 < 2 {
+            areas.push(first);
+            area = second;
+        } else {
+            areas.push(second);
+            area = first;
+        }
+    }
+
+    areas.push(area);
+
+    areas
+}
@@ -1,6 +1,9 @@
+mod backend;
 mod config;
 mod event;
+mod layout;
 mod log;
+mod rules;
 mod server;
 mod startup;
 mod wm;
@@ -8,12 +11,14 @@ mod wm;
 use log::{Output, Severity};
 use wm::WindowManager;
 
+use std::env;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // TODO: change this to use HOME
+    let home = env::var("HOME")?;
 
     log::init(vec![
         Output::stdout()?,
-        Output::file("/home/proxin/.config/yaxiwm/log.txt")?,
+        Output::file(&format!("{home}/.config/yaxiwm/log.txt"))?,
     ])?;
 
     log::write("starting yaxiwm\n", Severity::Info)?;
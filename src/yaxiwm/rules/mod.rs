@@ -0,0 +1,267 @@
+use crate::wm::Area;
+
+use yaxi::ewmh::EwmhWindowType;
+
+const TYPES: [(&str, EwmhWindowType); 8] = [
+    ("desktop", EwmhWindowType::Desktop),
+    ("dock", EwmhWindowType::Dock),
+    ("toolbar", EwmhWindowType::Toolbar),
+    ("menu", EwmhWindowType::Menu),
+    ("utility", EwmhWindowType::Utility),
+    ("splash", EwmhWindowType::Splash),
+    ("dialog", EwmhWindowType::Dialog),
+    ("normal", EwmhWindowType::Normal),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Tiled,
+    Float,
+    Fullscreen,
+}
+
+impl Placement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Placement::Tiled => "tiled",
+            Placement::Float => "float",
+            Placement::Fullscreen => "fullscreen",
+        }
+    }
+}
+
+impl TryFrom<&str> for Placement {
+    type Error = String;
+
+    fn try_from(name: &str) -> Result<Placement, String> {
+        [Placement::Tiled, Placement::Float, Placement::Fullscreen]
+            .into_iter()
+            .find(|placement| placement.name() == name)
+            .ok_or_else(|| format!("unknown state: {}", name))
+    }
+}
+
+// what a rule can match on, read from the window when it maps
+#[derive(Debug, Default, Clone)]
+pub struct Properties {
+    pub class: String,
+    pub instance: String,
+    pub name: String,
+    pub role: String,
+    pub type_: Vec<EwmhWindowType>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Actions {
+    pub placement: Option<Placement>,
+    pub workspace: Option<u32>,
+    pub monitor: Option<u32>,
+    pub geometry: Option<Area>,
+    pub focus: Option<bool>,
+    pub border_width: Option<u16>,
+    pub scratchpad: Option<String>,
+}
+
+impl Actions {
+    // later rules win over earlier ones
+    fn merge(self, other: Actions) -> Actions {
+        Actions {
+            placement: other.placement.or(self.placement),
+            workspace: other.workspace.or(self.workspace),
+            monitor: other.monitor.or(self.monitor),
+            geometry: other.geometry.or(self.geometry),
+            focus: other.focus.or(self.focus),
+            border_width: other.border_width.or(self.border_width),
+            scratchpad: other.scratchpad.or(self.scratchpad),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Rule {
+    pub class: Option<String>,
+    pub instance: Option<String>,
+    pub name: Option<String>,
+    pub role: Option<String>,
+    pub type_: Option<EwmhWindowType>,
+    pub actions: Actions,
+}
+
+impl Rule {
+    // space separated `key=value` pairs, values with spaces are quoted
+    pub fn parse(source: &str) -> Result<Rule, String> {
+        let mut rule = Rule::default();
+
+        for pair in split(source)? {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, found {}", pair))?;
+
+            rule.set(key, value)?;
+        }
+
+        rule.validate()?;
+
+        Ok(rule)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "class" => self.class = Some(value.to_string()),
+            "instance" => self.instance = Some(value.to_string()),
+            "name" => self.name = Some(value.to_string()),
+            "role" => self.role = Some(value.to_string()),
+            "type" => {
+                self.type_ = Some(
+                    TYPES
+                        .iter()
+                        .find(|(name, _)| *name == value)
+                        .map(|(_, type_)| *type_)
+                        .ok_or_else(|| format!("unknown type: {}", value))?,
+                );
+            }
+            "state" => self.actions.placement = Some(Placement::try_from(value)?),
+            "workspace" => self.actions.workspace = Some(index(key, value)?),
+            "monitor" => self.actions.monitor = Some(index(key, value)?),
+            "geometry" => {
+                let values = value
+                    .split(',')
+                    .map(|value| value.trim().parse::<u16>())
+                    .collect::<Result<Vec<u16>, _>>()
+                    .map_err(|_| format!("invalid geometry: {}", value))?;
+
+                let [x, y, width, height] = values[..] else {
+                    return Err(format!("geometry needs x,y,width,height, found {}", value));
+                };
+
+                self.actions.geometry = Some(Area::new(x, y, width, height));
+            }
+            "focus" => {
+                self.actions.focus = Some(
+                    value
+                        .parse::<bool>()
+                        .map_err(|_| format!("expected true or false, found {}", value))?,
+                );
+            }
+            "border-width" => {
+                self.actions.border_width = Some(
+                    value
+                        .parse::<u16>()
+                        .map_err(|_| format!("invalid border width: {}", value))?,
+                );
+            }
+            "scratchpad" => self.actions.scratchpad = Some(value.to_string()),
+            _ => return Err(format!("unknown rule key: {}", key)),
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.class.is_none()
+            && self.instance.is_none()
+            && self.name.is_none()
+            && self.role.is_none()
+            && self.type_.is_none()
+        {
+            return Err(String::from("rule must match on class, instance, name, role or type"));
+        }
+
+        Ok(())
+    }
+
+    pub fn matches(&self, properties: &Properties) -> bool {
+        [
+            (&self.class, &properties.class),
+            (&self.instance, &properties.instance),
+            (&self.name, &properties.name),
+            (&self.role, &properties.role),
+        ]
+        .into_iter()
+        .all(|(expected, value)| expected.as_ref().is_none_or(|expected| expected == value))
+            && self.type_.is_none_or(|type_| properties.type_.contains(&type_))
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let type_ = self.type_.and_then(|type_| {
+            TYPES
+                .iter()
+                .find(|(_, other)| *other == type_)
+                .map(|(name, _)| name.to_string())
+        });
+
+        let actions = &self.actions;
+
+        let pairs = [
+            ("class", self.class.clone()),
+            ("instance", self.instance.clone()),
+            ("name", self.name.clone()),
+            ("role", self.role.clone()),
+            ("type", type_),
+            ("state", actions.placement.map(|placement| placement.name().to_string())),
+            ("workspace", actions.workspace.map(|workspace| workspace.to_string())),
+            ("monitor", actions.monitor.map(|monitor| monitor.to_string())),
+            ("geometry", actions.geometry.map(|area| {
+                format!("{},{},{},{}", area.x, area.y, area.width, area.height)
+            })),
+            ("focus", actions.focus.map(|focus| focus.to_string())),
+            ("border-width", actions.border_width.map(|width| width.to_string())),
+            ("scratchpad", actions.scratchpad.clone()),
+        ];
+
+        let pairs = pairs
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| (key, value)))
+            .map(|(key, value)| match value.contains(char::is_whitespace) {
+                true => format!("{}=\"{}\"", key, value),
+                false => format!("{}={}", key, value),
+            })
+            .collect::<Vec<String>>();
+
+        f.write_str(&pairs.join(" "))
+    }
+}
+
+pub fn actions(rules: &[Rule], properties: &Properties) -> Actions {
+    rules
+        .iter()
+        .filter(|rule| rule.matches(properties))
+        .fold(Actions::default(), |actions, rule| actions.merge(rule.actions.clone()))
+}
+
+fn index(key: &str, value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(0) | Err(_) => Err(format!("{} must be a number starting at 1, found {}", key, value)),
+        Ok(index) => Ok(index),
+    }
+}
+
+fn split(source: &str) -> Result<Vec<String>, String> {
+    let mut pairs = Vec::new();
+    let mut pair = String::new();
+    let mut quoted = false;
+
+    for c in source.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !pair.is_empty() {
+                    pairs.push(std::mem::take(&mut pair));
+                }
+            }
+            c => pair.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(String::from("unterminated quote"));
+    }
+
+    if !pair.is_empty() {
+        pairs.push(pair);
+    }
+
+    Ok(pairs)
+}
@@ -5,8 +5,9 @@ use std::env;
 use std::fs;
 use std::os::unix::net::UnixListener;
 use std::thread;
+use std::time::Duration;
 
-use proto::{Sequence, Stream};
+use proto::{Notification, Protocol, Sequence, Stream};
 
 
 pub struct Listener {
@@ -29,14 +30,29 @@ impl Listener {
     }
 
     fn handle(&self, mut stream: Stream) -> Result<(), Box<dyn std::error::Error>> {
-        let events = stream
-            .read()?
-            .chunks(5)
-            .filter(|chunk| chunk.len() == 5)
-            .map(|chunk| EventType::Config(Sequence::decode(chunk)))
-            .collect::<Vec<EventType>>();
+        match stream.accept()? {
+            Protocol::Framed => {
+                while let Some(frame) = stream.frame()? {
+                    match Sequence::decode(&frame) {
+                        Ok(sequence) => {
+                            self.events
+                                .push(EventType::Config(sequence, stream.try_clone()?))?;
+                        }
+                        Err(err) => {
+                            log::write(format!("invalid sequence: {}\n", err), Severity::Error)?;
+                        }
+                    }
+                }
+            }
+            Protocol::Legacy(sequences) => {
+                let events = sequences
+                    .into_iter()
+                    .map(|sequence| Ok(EventType::Config(sequence, stream.try_clone()?)))
+                    .collect::<Result<Vec<EventType>, Box<dyn std::error::Error>>>()?;
 
-        self.events.extend(events)?;
+                self.events.extend(events)?;
+            }
+        }
 
         Ok(())
     }
@@ -45,11 +61,47 @@ impl Listener {
         for stream in self.listener.incoming() {
             let stream = Stream::from(stream?);
 
-            self.handle(stream)?;
+            if let Err(err) = self.handle(stream) {
+                log::write(format!("client failed: {}\n", err), Severity::Error)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Subscriber {
+    topics: u32,
+    stream: Stream,
+}
+
+pub struct Subscribers {
+    subscribers: Vec<Subscriber>,
+}
+
+impl Subscribers {
+    pub fn new() -> Subscribers {
+        Subscribers {
+            subscribers: Vec::new(),
         }
+    }
+
+    pub fn subscribe(&mut self, topics: u32, stream: Stream) -> Result<(), Box<dyn std::error::Error>> {
+        // a subscriber that stops reading must not be able to stall the window manager
+        stream.set_write_timeout(Some(Duration::from_millis(100)))?;
+
+        self.subscribers.push(Subscriber { topics, stream });
 
         Ok(())
     }
+
+    pub fn emit(&mut self, notification: Notification) {
+        let topic = notification.topic() as u32;
+
+        self.subscribers.retain_mut(|subscriber| {
+            subscriber.topics & topic == 0 || subscriber.stream.notify(&notification).is_ok()
+        });
+    }
 }
 
 pub fn listen(events: EventQueue) -> Result<(), Box<dyn std::error::Error>> {
@@ -1,5 +1,6 @@
 use std::env;
 use std::process::Command;
+use std::thread;
 
 pub fn startup() -> Result<(), Box<dyn std::error::Error>> {
     let home = env::var("HOME")?;
@@ -12,3 +13,14 @@ pub fn startup() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+// the child is reaped in the background so it doesn't linger as a zombie,
+// sh execs a simple command so the pid is that of the program itself
+pub fn spawn(command: &str) -> Result<u32, Box<dyn std::error::Error>> {
+    let mut child = Command::new("sh").arg("-c").arg(command).spawn()?;
+    let pid = child.id();
+
+    thread::spawn(move || child.wait());
+
+    Ok(pid)
+}
@@ -1,22 +1,40 @@
-use crate::config::{Config, Padding};
+use crate::config::{Config, FocusScope, Padding};
 use crate::log::{self, Severity};
 use crate::event::{EventQueue, EventType};
+use crate::layout::{Layout, Master};
+use crate::rules::{self, Placement, Properties, Rule};
 use crate::startup;
-use crate::server;
+use crate::server::{self, Subscribers};
+use crate::backend::{Backend, Handle, X11};
 
 use yaxi::display::request::GetGeometryResponse;
-use yaxi::display::{self, Atom, Display};
-use yaxi::ewmh::{EwmhWindowType, DesktopViewport};
+use yaxi::display::{Atom, Display};
+use yaxi::ewmh::EwmhWindowType;
 use yaxi::proto::{
     Button, ClientMessageData, Cursor, Event, EventKind, EventMask, KeyMask, KeyboardMode,
     PointerMode, RevertTo, WindowClass,
 };
 use yaxi::window::{ValuesBuilder, Window, WindowArguments, WindowKind};
 
-use proto::{Request, Sequence};
+use proto::{Notification, Reply, Request, Sequence, Status};
 
 use std::thread;
 
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_ADD: u32 = 1;
+
+const ALL_DESKTOPS: u32 = 0xffffffff;
+
+const WM_STATE_WITHDRAWN: u32 = 0;
+const WM_STATE_NORMAL: u32 = 1;
+const WM_STATE_ICONIC: u32 = 3;
+
+const P_MIN_SIZE: u32 = 1 << 4;
+const P_MAX_SIZE: u32 = 1 << 5;
+const P_RESIZE_INC: u32 = 1 << 6;
+const P_ASPECT: u32 = 1 << 7;
+const P_BASE_SIZE: u32 = 1 << 8;
+
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum State {
@@ -42,24 +60,196 @@ impl From<&[EwmhWindowType]> for State {
     }
 }
 
-pub struct Client {
-    window: Window,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WmState {
+    Fullscreen,
+    Above,
+    Below,
+    Sticky,
+    Hidden,
+    DemandsAttention,
+    MaximizedVert,
+    MaximizedHorz,
+}
+
+impl WmState {
+    pub const ALL: [WmState; 8] = [
+        WmState::Fullscreen,
+        WmState::Above,
+        WmState::Below,
+        WmState::Sticky,
+        WmState::Hidden,
+        WmState::DemandsAttention,
+        WmState::MaximizedVert,
+        WmState::MaximizedHorz,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WmState::Fullscreen => "_NET_WM_STATE_FULLSCREEN",
+            WmState::Above => "_NET_WM_STATE_ABOVE",
+            WmState::Below => "_NET_WM_STATE_BELOW",
+            WmState::Sticky => "_NET_WM_STATE_STICKY",
+            WmState::Hidden => "_NET_WM_STATE_HIDDEN",
+            WmState::DemandsAttention => "_NET_WM_STATE_DEMANDS_ATTENTION",
+            WmState::MaximizedVert => "_NET_WM_STATE_MAXIMIZED_VERT",
+            WmState::MaximizedHorz => "_NET_WM_STATE_MAXIMIZED_HORZ",
+        }
+    }
+}
+
+pub struct Client<W: Handle = Window> {
+    window: W,
     state: State,
+    states: Vec<WmState>,
+    geometry: Option<Area>,
+    border_width: Option<u16>,
+    hints: SizeHints,
+    transient_for: Option<u32>,
+    scratchpad: Option<String>,
+    mapped: bool,
+    ignore_unmaps: u32,
+}
+
+impl<W: Handle> Client<W> {
+    // clients are created from map requests before their window is mapped, tiling maps them
+    pub fn new(window: W, state: State) -> Client<W> {
+        Client {
+            window,
+            state,
+            states: Vec::new(),
+            geometry: None,
+            border_width: None,
+            hints: SizeHints::default(),
+            transient_for: None,
+            scratchpad: None,
+            mapped: false,
+            ignore_unmaps: 0,
+        }
+    }
+
+    pub fn show(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.mapped {
+            self.window.map()?;
+
+            self.mapped = true;
+        }
+
+        Ok(())
+    }
+
+    // every unmap we cause produces an unmap notify that must not be mistaken for the client withdrawing
+    pub fn hide(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.mapped {
+            self.window.unmap()?;
+
+            self.mapped = false;
+            self.ignore_unmaps += 1;
+        }
+
+        Ok(())
+    }
+
+    pub fn expects_unmap(&mut self) -> bool {
+        let expected = self.ignore_unmaps > 0;
+
+        self.ignore_unmaps = self.ignore_unmaps.saturating_sub(1);
+
+        expected
+    }
+
+    pub fn wm_state(&self) -> u32 {
+        if self.mapped { WM_STATE_NORMAL } else { WM_STATE_ICONIC }
+    }
+
+    // rules can override the configured border width per client
+    pub fn border_width(&self, default: u16) -> u16 {
+        if self.has(WmState::Fullscreen) { 0 } else { self.border_width.unwrap_or(default) }
+    }
+
+    pub fn with_states(mut self, states: Vec<WmState>) -> Client<W> {
+        self.states = states;
+
+        self
+    }
+
+    pub fn has(&self, state: WmState) -> bool {
+        self.states.contains(&state)
+    }
+
+    pub fn set(&mut self, state: WmState, enabled: bool) {
+        if enabled && !self.has(state) {
+            self.states.push(state);
+        } else if !enabled {
+            self.states.retain(|other| *other != state);
+        }
+    }
+
+    pub fn is_tiled(&self) -> bool {
+        self.state == State::Tiled && !self.has(WmState::Fullscreen) && !self.has(WmState::Hidden)
+    }
+
+    pub fn in_scope(&self, scope: FocusScope) -> bool {
+        match scope {
+            FocusScope::All => self.state != State::Dock && !self.has(WmState::Hidden),
+            FocusScope::Tiled => self.is_tiled(),
+            FocusScope::Floating => self.state == State::Float && !self.has(WmState::Hidden),
+        }
+    }
+
+    pub fn layer(&self) -> u8 {
+        if self.has(WmState::Fullscreen) {
+            3
+        } else if self.has(WmState::Above) {
+            2
+        } else if self.has(WmState::Below) {
+            0
+        } else {
+            1
+        }
+    }
+
+    pub fn overrides_geometry(&self) -> bool {
+        self.has(WmState::Fullscreen)
+            || self.has(WmState::MaximizedVert)
+            || self.has(WmState::MaximizedHorz)
+    }
 }
 
-impl Client {
-    pub fn new(window: Window, state: State) -> Client {
-        Client { window, state }
+pub struct Workspace<W: Handle = Window> {
+    clients: Vec<Client<W>>,
+    layout: Layout,
+    master: Master,
+}
+
+impl<W: Handle> Workspace<W> {
+    pub fn new() -> Workspace<W> {
+        Workspace {
+            clients: Vec::new(),
+            layout: Layout::default(),
+            master: Master::default(),
+        }
+    }
+
+    // transients stay above their parent, even a fullscreen one
+    fn raise_transients(&self, parent: u32) -> Result<(), Box<dyn std::error::Error>> {
+        for client in self.clients.iter().filter(|client| client.transient_for == Some(parent)) {
+            client.window.raise()?;
+
+            self.raise_transients(client.window.id())?;
+        }
+
+        Ok(())
     }
 }
 
-pub struct Workspaces {
-    workspaces: Vec<Vec<Client>>,
+pub struct Workspaces<W: Handle = Window> {
+    workspaces: Vec<Workspace<W>>,
     current: usize,
 }
 
-impl Workspaces {
-    pub fn new() -> Workspaces {
+impl<W: Handle> Workspaces<W> {
+    pub fn new() -> Workspaces<W> {
         Workspaces {
             workspaces: Vec::new(),
             current: 0,
@@ -68,65 +258,185 @@ impl Workspaces {
 
     pub fn resize(&mut self, size: usize) {
         if size >= self.len() {
-            self.workspaces.resize_with(size, Vec::new);
+            self.workspaces.resize_with(size, Workspace::new);
         } else if size > 0 {
             let excess = self
                 .workspaces
                 .drain(size..self.len())
-                .flatten()
-                .collect::<Vec<Client>>();
+                .flat_map(|workspace| workspace.clients)
+                .collect::<Vec<Client<W>>>();
 
-            self.workspaces[size - 1].extend(excess);
+            self.workspaces[size - 1].clients.extend(excess);
 
-            self.workspaces.truncate(self.len() - size);
+            self.current = self.current.min(size - 1);
         }
     }
 
+    // sticky clients are on every workspace, so they move along to the new current one
+    pub fn set_current(&mut self, workspace: usize) {
+        let (sticky, clients) = std::mem::take(&mut self.workspaces[self.current].clients)
+            .into_iter()
+            .partition::<Vec<Client<W>>, _>(|client| client.has(WmState::Sticky));
+
+        self.workspaces[self.current].clients = clients;
+        self.current = workspace;
+        self.workspaces[workspace].clients.extend(sticky);
+    }
+
     pub fn len(&self) -> usize {
         self.workspaces.len()
     }
 
-    pub fn insert(&mut self, client: Client) {
-        self.workspaces[self.current].push(client);
+    pub fn insert(&mut self, client: Client<W>) {
+        self.workspaces[self.current].clients.push(client);
+    }
+
+    pub fn insert_at(&mut self, workspace: usize, client: Client<W>) {
+        self.workspaces[workspace].clients.push(client);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Client<W> {
+        self.workspaces[self.current].clients.remove(index)
     }
 
-    pub fn remove(&mut self, index: usize) -> Client {
-        self.workspaces[self.current].remove(index)
+    pub fn take(&mut self, wid: u32) -> Option<Client<W>> {
+        self.workspaces.iter_mut().find_map(|workspace| {
+            workspace
+                .clients
+                .iter()
+                .position(|client| client.window.id() == wid)
+                .map(|index| workspace.clients.remove(index))
+        })
     }
 
     pub fn find(&self, wid: u32) -> Option<usize> {
         self.workspaces[self.current]
+            .clients
             .iter()
             .position(|client| client.window.id() == wid)
     }
 
+    pub fn position(&self, wid: u32) -> Option<usize> {
+        self.workspaces.iter().position(|workspace| {
+            workspace
+                .clients
+                .iter()
+                .any(|client| client.window.id() == wid)
+        })
+    }
+
+    pub fn client_mut(&mut self, wid: u32) -> Option<&mut Client<W>> {
+        self.workspaces
+            .iter_mut()
+            .flat_map(|workspace| workspace.clients.iter_mut())
+            .find(|client| client.window.id() == wid)
+    }
+
+    pub fn clients(&self) -> impl Iterator<Item = &Client<W>> {
+        self.workspaces
+            .iter()
+            .flat_map(|workspace| workspace.clients.iter())
+    }
+
     pub fn is_float(&self, wid: u32) -> bool {
         match self.find(wid) {
-            Some(index) => self.workspaces[self.current][index].state == State::Float,
+            Some(index) => self.workspaces[self.current].clients[index].state == State::Float,
             None => false,
         }
     }
 
-    pub fn change_focus<F>(&mut self, wid: u32, f: F) -> Result<(), Box<dyn std::error::Error>>
+    // `f` maps the position of the client among those in `scope`, if it is one of them, and their count to the one to focus
+    pub fn change_focus<F>(&mut self, wid: u32, scope: FocusScope, f: F) -> Result<bool, Box<dyn std::error::Error>>
     where
-        F: Fn(usize) -> usize,
+        F: Fn(Option<usize>, usize) -> usize,
     {
-        if let Some(client) = self
-            .find(wid)
-            .and_then(|index| self.workspaces[self.current].get_mut(f(index)))
-        {
-            client.window.set_input_focus(RevertTo::Parent)?;
+        let Some(index) = self.find(wid) else {
+            return Ok(false);
+        };
+
+        let clients = &self.workspaces[self.current].clients;
+
+        let candidates = clients
+            .iter()
+            .enumerate()
+            .filter(|(_, client)| client.in_scope(scope))
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+
+        if candidates.is_empty() {
+            return Ok(true);
         }
 
-        Ok(())
+        let position = candidates.iter().position(|other| *other == index);
+
+        clients[candidates[f(position, candidates.len()) % candidates.len()]]
+            .window
+            .focus()?;
+
+        Ok(true)
+    }
+
+    // positions of the tiled clients of the current workspace, in layout order
+    fn tiled(&self) -> Vec<usize> {
+        self.workspaces[self.current]
+            .clients
+            .iter()
+            .enumerate()
+            .filter(|(_, client)| client.is_tiled())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    // `f` maps the position of the client among the tiled ones and their count to the position it swaps with
+    pub fn swap<F>(&mut self, wid: u32, f: F) -> bool
+    where
+        F: Fn(usize, usize) -> Option<usize>,
+    {
+        let Some(index) = self.find(wid) else {
+            return false;
+        };
+
+        let tiled = self.tiled();
+
+        let target = tiled
+            .iter()
+            .position(|other| *other == index)
+            .and_then(|position| f(position, tiled.len()))
+            .and_then(|position| tiled.get(position));
+
+        if let Some(target) = target {
+            self.workspaces[self.current].clients.swap(index, *target);
+        }
+
+        true
+    }
+
+    // clients shown on the current workspace and where they are, only the tiled ones if `tiled` is set
+    pub fn geometries(&self, tiled: bool) -> Result<Vec<(u32, Area)>, Box<dyn std::error::Error>> {
+        self.workspaces[self.current]
+            .clients
+            .iter()
+            .filter(|client| !client.has(WmState::Hidden) && (!tiled || client.is_tiled()))
+            .map(|client| Ok((client.window.id(), client.window.geometry()?)))
+            .collect()
+    }
+
+    // every tiled client moves one position down the stack, the last one becomes master
+    pub fn rotate(&mut self) {
+        let tiled = self.tiled();
+        let clients = &mut self.workspaces[self.current].clients;
+
+        for pair in tiled.windows(2).rev() {
+            clients.swap(pair[0], pair[1]);
+        }
     }
 
     pub fn map_clients<F>(&mut self, f: F) -> Result<(), Box<dyn std::error::Error>>
     where
-        F: Fn(&mut Client) -> Result<(), Box<dyn std::error::Error>>,
+        F: Fn(&mut Client<W>) -> Result<(), Box<dyn std::error::Error>>,
     {
         for workspace in self.workspaces.iter_mut() {
-            for client in workspace {
+            for client in workspace.clients.iter_mut() {
                 f(client)?;
             }
         }
@@ -134,45 +444,115 @@ impl Workspaces {
         Ok(())
     }
 
-    pub fn tile(&mut self, mut area: Area, gaps: u16) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn layout(&self) -> Layout {
+        self.workspaces
+            .get(self.current)
+            .map(|workspace| workspace.layout)
+            .unwrap_or_default()
+    }
+
+    pub fn set_layout(&mut self, layout: Layout) {
+        if let Some(workspace) = self.workspaces.get_mut(self.current) {
+            workspace.layout = layout;
+        }
+    }
+
+    pub fn master(&self) -> Master {
+        self.workspaces
+            .get(self.current)
+            .map(|workspace| workspace.master)
+            .unwrap_or_default()
+    }
+
+    pub fn set_master(&mut self, master: Master) {
+        if let Some(workspace) = self.workspaces.get_mut(self.current) {
+            workspace.master = master;
+        }
+    }
+
+    pub fn tile(&mut self, screen: Area, area: Area, gaps: u16, size_hints: bool) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(workspace) = self.workspaces.get_mut(self.current) {
-            let ignored = workspace
+            let tiled = workspace
+                .clients
                 .iter()
-                .map(|client| client.state != State::Tiled)
-                .collect::<Vec<bool>>();
+                .filter(|client| client.is_tiled())
+                .count();
 
-            for (index, client) in workspace.iter_mut().enumerate() {
-                match client.state {
-                    State::Tiled => {
-                        let tiled_clients_left = ignored[index + 1..]
-                            .iter()
-                            .filter(|ignore| !**ignore)
-                            .count();
-
-                        let win = (tiled_clients_left > 0)
-                            .then(|| area.split())
-                            .unwrap_or(area);
-
-                        client.window.mov_resize(
-                            win.x + gaps,
-                            win.y + gaps,
-                            win.width - (gaps * 2),
-                            win.height - (gaps * 2),
-                        )?;
+            let mut areas = workspace
+                .layout
+                .arrange(area, tiled, workspace.master)
+                .into_iter();
+
+            for client in workspace.clients.iter_mut() {
+                if client.has(WmState::Hidden) {
+                    client.hide()?;
+
+                    continue;
+                }
+
+                if client.has(WmState::Fullscreen) {
+                    client.window.set_border_width(0)?;
+
+                    client
+                        .window
+                        .mov_resize(screen.x, screen.y, screen.width, screen.height)?;
+                } else if client.state == State::Tiled {
+                    if let Some(win) = areas.next() {
+                        let width = win.width.saturating_sub(gaps * 2).max(1);
+                        let height = win.height.saturating_sub(gaps * 2).max(1);
+
+                        let (width, height) = if size_hints {
+                            client.hints.constrain(width, height, false)
+                        } else {
+                            (width, height)
+                        };
+
+                        client.window.mov_resize(win.x + gaps, win.y + gaps, width, height)?;
                     }
-                    _ => {}
+                } else if client.overrides_geometry() {
+                    let geometry = client.window.geometry()?;
+
+                    let (x, width) = if client.has(WmState::MaximizedHorz) {
+                        (area.x, area.width)
+                    } else {
+                        (geometry.x, geometry.width)
+                    };
+
+                    let (y, height) = if client.has(WmState::MaximizedVert) {
+                        (area.y, area.height)
+                    } else {
+                        (geometry.y, geometry.height)
+                    };
+
+                    client.window.mov_resize(x, y, width, height)?;
                 }
 
-                client.window.map(WindowKind::Window)?;
+                client.show()?;
+            }
+
+            for client in workspace.clients.iter().filter(|client| client.has(WmState::Below)) {
+                client.window.lower()?;
+            }
+
+            for state in [WmState::Above, WmState::Fullscreen] {
+                for client in workspace
+                    .clients
+                    .iter()
+                    .filter(|client| client.has(state) && !client.has(WmState::Hidden))
+                {
+                    client.window.raise()?;
+
+                    workspace.raise_transients(client.window.id())?;
+                }
             }
         }
 
         for (w_idx, workspace) in self.workspaces.iter_mut().enumerate() {
             if w_idx != self.current {
-                for client in workspace {
+                for client in workspace.clients.iter_mut() {
                     match client.state {
                         State::Tiled | State::Float => {
-                            client.window.unmap(WindowKind::Window)?;
+                            client.hide()?;
                         }
                         _ => {}
                     }
@@ -184,12 +564,12 @@ impl Workspaces {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Area {
-    x: u16,
-    y: u16,
-    width: u16,
-    height: u16,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
 }
 
 impl Area {
@@ -203,15 +583,15 @@ impl Area {
     }
 
     pub fn contains(&self, x: u16, y: u16) -> bool {
-        (x >= self.x && self.y >= self.y) && (self.x + self.width > x && self.y + self.height > y)
+        (x >= self.x && y >= self.y) && (self.x + self.width > x && self.y + self.height > y)
     }
 
     pub fn pad(&self, padding: Padding) -> Area {
         Area {
             x: self.x + padding.left,
             y: self.y + padding.top,
-            width: self.width - padding.right - padding.left,
-            height: self.height - padding.bottom - padding.top,
+            width: self.width.saturating_sub(padding.right + padding.left),
+            height: self.height.saturating_sub(padding.bottom + padding.top),
         }
     }
 
@@ -240,692 +620,3129 @@ impl Area {
     }
 }
 
-pub struct Monitor {
-    area: Area,
-    workspace: Workspaces,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
 }
 
-pub struct Monitors {
-    monitors: Vec<Monitor>,
-    root: Window,
-}
+impl TryFrom<&str> for Direction {
+    type Error = String;
 
-impl Monitors {
-    pub fn new(root: Window) -> Monitors {
-        Monitors {
-            monitors: Vec::new(),
-            root,
+    fn try_from(name: &str) -> Result<Direction, String> {
+        match name {
+            "left" => Ok(Direction::Left),
+            "right" => Ok(Direction::Right),
+            "up" => Ok(Direction::Up),
+            "down" => Ok(Direction::Down),
+            _ => Err(format!("unknown direction: {}", name)),
         }
     }
+}
 
-    pub fn append(&mut self, monitor: Monitor) {
-        self.monitors.push(monitor);
-    }
+impl Direction {
+    // how far `to` lies from `from` going this way, areas that share a row or column sort first
+    pub fn distance(&self, from: Area, to: Area) -> Option<(bool, u32)> {
+        let center = |area: Area| {
+            (
+                area.x as i32 + area.width as i32 / 2,
+                area.y as i32 + area.height as i32 / 2,
+            )
+        };
 
-    pub fn is_tiled(&mut self, wid: u32) -> bool {
-        self.monitors
-            .iter()
-            .map(|monitor| monitor.workspace.is_float(wid))
-            .any(|float| !float)
+        let overlaps = |start: u16, length: u16, other: u16, other_length: u16| {
+            (start as i32) < other as i32 + other_length as i32
+                && (other as i32) < start as i32 + length as i32
+        };
+
+        let ((x, y), (other_x, other_y)) = (center(from), center(to));
+
+        let (primary, secondary, aligned) = match self {
+            Direction::Left | Direction::Right => (
+                other_x - x,
+                other_y - y,
+                overlaps(from.y, from.height, to.y, to.height),
+            ),
+            Direction::Up | Direction::Down => (
+                other_y - y,
+                other_x - x,
+                overlaps(from.x, from.width, to.x, to.width),
+            ),
+        };
+
+        let primary = match self {
+            Direction::Left | Direction::Up => -primary,
+            Direction::Right | Direction::Down => primary,
+        };
+
+        (primary > 0).then(|| (!aligned, (primary + secondary.abs()) as u32))
     }
+}
 
-    pub fn extract_client(&mut self, wid: u32) -> Result<Option<(usize, Client)>, Box<dyn std::error::Error>> {
-        let mut client: Option<(usize, Client)> = None;
+// what lies in a direction from a client, the adjacent monitor is only used when its own has nothing there
+enum Neighbour {
+    Client { monitor: usize, window: u32 },
+    Monitor(usize),
+    Edge,
+}
 
-        self.all(|count, monitor|  {
-            if let Some(index) = monitor.workspace.find(wid) {
-                client.replace((count, monitor.workspace.remove(index)));
-            }
+// WM_NORMAL_HINTS, a max of zero means unbounded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SizeHints {
+    min: (u32, u32),
+    max: (u32, u32),
+    base: (u32, u32),
+    increment: (u32, u32),
+    min_aspect: (u32, u32),
+    max_aspect: (u32, u32),
+}
 
-            Ok(())
-        })?;
+impl SizeHints {
+    pub fn new(values: &[u32]) -> SizeHints {
+        if values.len() < 15 {
+            return SizeHints::default();
+        }
 
-        Ok(client)
-    }
+        let flags = values[0];
+        let pair = |set: bool, first: usize| if set { (values[first], values[first + 1]) } else { (0, 0) };
 
-    pub fn focused<F>(&mut self, mut f: F) -> Result<(), Box<dyn std::error::Error>>
-    where
-        F: FnMut(usize, &mut Monitor) -> Result<(), Box<dyn std::error::Error>>,
-    {
-        let pointer = self.root.query_pointer()?;
+        let min = pair(flags & P_MIN_SIZE != 0, 5);
+        let base = pair(flags & P_BASE_SIZE != 0 && values.len() >= 17, 15);
 
-        for (index, monitor) in self.monitors.iter_mut().enumerate() {
-            if monitor.area.contains(pointer.root_x, pointer.root_y) {
-                f(index, monitor)?;
-            }
+        // each of the min and base size stands in for the other when missing
+        SizeHints {
+            min: if flags & P_MIN_SIZE != 0 { min } else { base },
+            max: pair(flags & P_MAX_SIZE != 0, 7),
+            base: if flags & P_BASE_SIZE != 0 { base } else { min },
+            increment: pair(flags & P_RESIZE_INC != 0, 9),
+            min_aspect: pair(flags & P_ASPECT != 0, 11),
+            max_aspect: pair(flags & P_ASPECT != 0, 13),
         }
+    }
 
-        Ok(())
+    pub fn is_fixed(&self) -> bool {
+        self.min.0 > 0 && self.min.1 > 0 && self.min == self.max
     }
 
-    pub fn all<F>(&mut self, mut f: F) -> Result<(), Box<dyn std::error::Error>>
-    where
-        F: FnMut(usize, &mut Monitor) -> Result<(), Box<dyn std::error::Error>>,
-    {
-        for (index, monitor) in self.monitors.iter_mut().enumerate() {
-            f(index, monitor)?;
+    pub fn constrain(&self, width: u16, height: u16, aspect: bool) -> (u16, u16) {
+        // hints come straight from the client, so the math is done wide enough not to overflow
+        let wide = |(x, y): (u32, u32)| (x as u64, y as u64);
+
+        let (min, max, base, increment) = (wide(self.min), wide(self.max), wide(self.base), wide(self.increment));
+        let (mut width, mut height) = (width as u64, height as u64);
+
+        // aspect ratios apply to the size without the base size
+        if aspect {
+            let (w, h) = (width.saturating_sub(base.0), height.saturating_sub(base.1));
+
+            let (w, h) = match (wide(self.min_aspect), wide(self.max_aspect)) {
+                (_, (x, y)) if x > 0 && y > 0 && w * y > h * x => (h * x / y, h),
+                ((x, y), _) if x > 0 && y > 0 && w * y < h * x => (w, w * y / x),
+                _ => (w, h),
+            };
+
+            (width, height) = (w + base.0, h + base.1);
         }
 
-        Ok(())
+        if increment.0 > 0 {
+            width -= width.saturating_sub(base.0) % increment.0;
+        }
+
+        if increment.1 > 0 {
+            height -= height.saturating_sub(base.1) % increment.1;
+        }
+
+        width = width.max(min.0);
+        height = height.max(min.1);
+
+        if max.0 > 0 {
+            width = width.min(max.0);
+        }
+
+        if max.1 > 0 {
+            height = height.min(max.1);
+        }
+
+        (width.clamp(1, u16::MAX as u64) as u16, height.clamp(1, u16::MAX as u64) as u16)
     }
 }
 
-pub struct Grab {
-    button: Button,
-    window: Window,
-    geometry: GetGeometryResponse,
-    x: u16,
-    y: u16,
+#[derive(Debug, Clone, Copy)]
+pub struct Strut {
+    left: u32,
+    right: u32,
+    top: u32,
+    bottom: u32,
+    left_y: (u32, u32),
+    right_y: (u32, u32),
+    top_x: (u32, u32),
+    bottom_x: (u32, u32),
 }
 
-impl Grab {
-    pub fn new(
-        button: Button,
-        window: Window,
-        geometry: GetGeometryResponse,
-        x: u16,
-        y: u16,
-    ) -> Grab {
-        Grab {
-            button,
-            window,
-            geometry,
-            x,
-            y,
+impl Strut {
+    pub fn full(values: &[u32]) -> Strut {
+        Strut {
+            left: values[0],
+            right: values[1],
+            top: values[2],
+            bottom: values[3],
+            left_y: (0, u32::MAX),
+            right_y: (0, u32::MAX),
+            top_x: (0, u32::MAX),
+            bottom_x: (0, u32::MAX),
         }
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct Atoms {
-    wm_delete: Atom,
-    wm_protocols: Atom,
-}
+    pub fn partial(values: &[u32]) -> Strut {
+        Strut {
+            left_y: (values[4], values[5]),
+            right_y: (values[6], values[7]),
+            top_x: (values[8], values[9]),
+            bottom_x: (values[10], values[11]),
+            ..Strut::full(values)
+        }
+    }
 
-pub struct WindowManager {
-    display: Display,
-    root: Window,
-    monitors: Monitors,
-    config: Config,
-    events: EventQueue,
-    atoms: Atoms,
-    grab: Option<Grab>,
-    should_close: bool,
-}
+    // struts are relative to the edges of the screen, so only the part that reaches into the monitor is reserved
+    pub fn reserve(&self, monitor: Area, screen: Area) -> Padding {
+        let overlaps = |(start, end): (u32, u32), from: u16, len: u16| {
+            start < from as u32 + len as u32 && end >= from as u32
+        };
 
-impl WindowManager {
-    pub fn new() -> Result<WindowManager, Box<dyn std::error::Error>> {
-        let display = display::open(None)?;
-        let root = display.default_root_window()?;
+        let (x, y) = (monitor.x as u32, monitor.y as u32);
+        let (right, bottom) = (x + monitor.width as u32, y + monitor.height as u32);
 
-        let atoms = Atoms {
-            wm_delete: display.intern_atom("WM_DELETE_WINDOW", false)?,
-            wm_protocols: display.intern_atom("WM_PROTOCOLS", false)?,
+        let left = if self.left > x && overlaps(self.left_y, monitor.y, monitor.height) {
+            self.left - x
+        } else {
+            0
         };
 
-        Ok(WindowManager {
-            display,
-            root: root.clone(),
-            monitors: Monitors::new(root),
-            config: Config::default(),
-            events: EventQueue::new(),
-            atoms,
-            grab: None,
-            should_close: false,
-        })
+        let top = if self.top > y && overlaps(self.top_x, monitor.x, monitor.width) {
+            self.top - y
+        } else {
+            0
+        };
+
+        let right = (screen.width as u32)
+            .checked_sub(self.right)
+            .filter(|edge| self.right > 0 && *edge < right && overlaps(self.right_y, monitor.y, monitor.height))
+            .map(|edge| right - edge.max(x))
+            .unwrap_or_default();
+
+        let bottom = (screen.height as u32)
+            .checked_sub(self.bottom)
+            .filter(|edge| self.bottom > 0 && *edge < bottom && overlaps(self.bottom_x, monitor.x, monitor.width))
+            .map(|edge| bottom - edge.max(y))
+            .unwrap_or_default();
+
+        Padding {
+            top: top.min(monitor.height as u32) as u16,
+            bottom: bottom.min(monitor.height as u32) as u16,
+            left: left.min(monitor.width as u32) as u16,
+            right: right.min(monitor.width as u32) as u16,
+        }
     }
+}
 
-    fn setup(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        self.root.select_input(&[
-            EventMask::SubstructureNotify,
-            EventMask::SubstructureRedirect,
-            EventMask::EnterWindow,
-            EventMask::FocusChange,
-        ])?;
+pub struct Dock<W: Handle = Window> {
+    window: W,
+    strut: Option<Strut>,
+}
 
-        for button in [Button::Button1, Button::Button3] {
-            self.root.grab_button(
-                button,
-                vec![KeyMask::Mod4],
-                vec![
-                    EventMask::ButtonPress,
-                    EventMask::ButtonRelease,
-                    EventMask::ButtonMotion,
-                ],
-                Cursor::Nop,
-                PointerMode::Asynchronous,
-                KeyboardMode::Asynchronous,
-                true,
-                0,
-            )?;
+impl<W: Handle> Dock<W> {
+    pub fn new(window: W, strut: Option<Strut>) -> Dock<W> {
+        Dock { window, strut }
+    }
+}
+
+pub struct Monitor<W: Handle = Window> {
+    area: Area,
+    reserved: Padding,
+    docks: Vec<Dock<W>>,
+    workspace: Workspaces<W>,
+}
+
+impl<W: Handle> Monitor<W> {
+    pub fn workarea(&self) -> Area {
+        self.area.pad(self.reserved)
+    }
+
+    pub fn tile(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        // docks sit above tiled windows but below clients that are above or fullscreen
+        for dock in &self.docks {
+            dock.window.raise()?;
         }
 
-        server::listen(self.events.clone())?;
+        self.workspace.tile(
+            self.area,
+            self.workarea().pad(config.padding),
+            config.windows.gaps,
+            config.windows.size_hints,
+        )
+    }
+}
 
-        self.set_supporting_ewmh()?;
+pub struct Monitors<B: Backend = X11> {
+    monitors: Vec<Monitor<B::Window>>,
+    // hidden scratchpads live outside of every workspace
+    scratchpads: Vec<Client<B::Window>>,
+    docks_hidden: bool,
+    backend: B,
+}
 
-        self.load_monitors()?;
+impl<B: Backend> Monitors<B> {
+    pub fn new(backend: B) -> Monitors<B> {
+        Monitors {
+            monitors: Vec::new(),
+            scratchpads: Vec::new(),
+            docks_hidden: false,
+            backend,
+        }
+    }
 
-        startup::startup()?;
+    pub fn scratchpad(&self, name: &str) -> Option<u32> {
+        self.monitors
+            .iter()
+            .flat_map(|monitor| monitor.workspace.clients())
+            .chain(self.scratchpads.iter())
+            .find(|client| client.scratchpad.as_deref() == Some(name))
+            .map(|client| client.window.id())
+    }
+
+    pub fn place_dock(&mut self, dock: Dock<B::Window>, x: u16, y: u16) {
+        self.remove_dock(dock.window.id());
+
+        let index = self
+            .monitors
+            .iter()
+            .position(|monitor| monitor.area.contains(x, y))
+            .unwrap_or_default();
+
+        if let Some(monitor) = self.monitors.get_mut(index) {
+            monitor.docks.push(dock);
+        }
+    }
+
+    pub fn remove_dock(&mut self, wid: u32) -> Option<Dock<B::Window>> {
+        self.monitors.iter_mut().find_map(|monitor| {
+            monitor
+                .docks
+                .iter()
+                .position(|dock| dock.window.id() == wid)
+                .map(|index| monitor.docks.remove(index))
+        })
+    }
+
+    pub fn dock_mut(&mut self, wid: u32) -> Option<&mut Dock<B::Window>> {
+        self.monitors
+            .iter_mut()
+            .flat_map(|monitor| monitor.docks.iter_mut())
+            .find(|dock| dock.window.id() == wid)
+    }
+
+    pub fn toggle_docks(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.docks_hidden = !self.docks_hidden;
+
+        for dock in self.monitors.iter().flat_map(|monitor| monitor.docks.iter()) {
+            if self.docks_hidden {
+                dock.window.unmap()?;
+            } else {
+                dock.window.map()?;
+            }
+        }
 
         Ok(())
     }
 
-    fn load_monitors(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let xinerama = self.display.query_xinerama()?;
+    pub fn reserve(&mut self, screen: Area) {
+        let struts = self
+            .monitors
+            .iter()
+            .flat_map(|monitor| monitor.docks.iter())
+            .filter_map(|dock| dock.strut)
+            .filter(|_| !self.docks_hidden)
+            .collect::<Vec<Strut>>();
+
+        for monitor in self.monitors.iter_mut() {
+            monitor.reserved = struts
+                .iter()
+                .map(|strut| strut.reserve(monitor.area, screen))
+                .fold(Padding::default(), |reserved, padding| Padding {
+                    top: reserved.top.max(padding.top),
+                    bottom: reserved.bottom.max(padding.bottom),
+                    left: reserved.left.max(padding.left),
+                    right: reserved.right.max(padding.right),
+                });
+        }
+    }
+
+    pub fn workarea(&self) -> Vec<u32> {
+        self.monitors
+            .iter()
+            .flat_map(|monitor| {
+                let area = monitor.workarea();
+
+                [area.x as u32, area.y as u32, area.width as u32, area.height as u32]
+                    .repeat(monitor.workspace.len())
+            })
+            .collect()
+    }
+
+    pub fn append(&mut self, monitor: Monitor<B::Window>) {
+        self.monitors.push(monitor);
+    }
+
+    pub fn is_tiled(&mut self, wid: u32) -> bool {
+        self.monitors
+            .iter()
+            .map(|monitor| monitor.workspace.is_float(wid))
+            .any(|float| !float)
+    }
+
+    pub fn clients(&self) -> Vec<u32> {
+        self.monitors
+            .iter()
+            .flat_map(|monitor| monitor.workspace.clients())
+            .map(|client| client.window.id())
+            .collect()
+    }
+
+    pub fn stacking(&self) -> Vec<u32> {
+        let mut clients = self
+            .monitors
+            .iter()
+            .flat_map(|monitor| monitor.workspace.clients())
+            .collect::<Vec<&Client<B::Window>>>();
+
+        clients.sort_by_key(|client| client.layer());
+
+        clients.iter().map(|client| client.window.id()).collect()
+    }
+
+    pub fn desktops(&self) -> Vec<(&Client<B::Window>, u32)> {
+        self.monitors
+            .iter()
+            .enumerate()
+            .flat_map(|(index, monitor)| {
+                let len = monitor.workspace.len();
+
+                monitor
+                    .workspace
+                    .workspaces
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(workspace, clients)| {
+                        clients
+                            .clients
+                            .iter()
+                            .map(move |client| (client, (len * index + workspace) as u32))
+                    })
+            })
+            .collect()
+    }
+
+    pub fn move_client(&mut self, wid: u32, index: usize, workspace: usize) -> bool {
+        if self
+            .monitors
+            .get(index)
+            .is_none_or(|monitor| workspace >= monitor.workspace.len())
+        {
+            return false;
+        }
+
+        let Some(client) = self.take_client(wid) else {
+            return false;
+        };
+
+        self.monitors[index].workspace.insert_at(workspace, client);
+
+        for transient in self.transients(wid) {
+            self.move_client(transient, index, workspace);
+        }
+
+        true
+    }
+
+    // swaps the places of two clients, which may be on different monitors or workspaces
+    pub fn exchange(&mut self, a: u32, b: u32) -> bool {
+        let position = |wid: u32| {
+            self.monitors.iter().enumerate().find_map(|(index, monitor)| {
+                monitor
+                    .workspace
+                    .workspaces
+                    .iter()
+                    .enumerate()
+                    .find_map(|(workspace, clients)| {
+                        clients
+                            .clients
+                            .iter()
+                            .position(|client| client.window.id() == wid)
+                            .map(|position| (index, workspace, position))
+                    })
+            })
+        };
+
+        let (Some(first), Some(second)) = (position(a), position(b)) else {
+            return false;
+        };
+
+        if (first.0, first.1) == (second.0, second.1) {
+            self.monitors[first.0].workspace.workspaces[first.1]
+                .clients
+                .swap(first.2, second.2);
+
+            return true;
+        }
+
+        let client = self.monitors[first.0].workspace.workspaces[first.1].clients.remove(first.2);
+
+        let other = std::mem::replace(
+            &mut self.monitors[second.0].workspace.workspaces[second.1].clients[second.2],
+            client,
+        );
+
+        self.monitors[first.0].workspace.workspaces[first.1]
+            .clients
+            .insert(first.2, other);
+
+        for (wid, (index, workspace, _)) in [(a, second), (b, first)] {
+            for transient in self.transients(wid) {
+                self.move_client(transient, index, workspace);
+            }
+        }
+
+        true
+    }
+
+    pub fn transients(&self, wid: u32) -> Vec<u32> {
+        self.desktops()
+            .into_iter()
+            .filter(|(client, _)| client.transient_for == Some(wid))
+            .map(|(client, _)| client.window.id())
+            .collect()
+    }
+
+    pub fn locate(&self, wid: u32) -> Option<(usize, usize)> {
+        self.monitors
+            .iter()
+            .enumerate()
+            .find_map(|(index, monitor)| Some((index, monitor.workspace.position(wid)?)))
+    }
+
+    pub fn focused_index(&self) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+        let (x, y) = self.backend.query_pointer()?;
 
-        let screens = xinerama.query_screens()?;
+        Ok(self
+            .monitors
+            .iter()
+            .position(|monitor| monitor.area.contains(x, y)))
+    }
+
+    pub fn client_mut(&mut self, wid: u32) -> Option<&mut Client<B::Window>> {
+        self.monitors
+            .iter_mut()
+            .find_map(|monitor| monitor.workspace.client_mut(wid))
+            .or_else(|| self.scratchpads.iter_mut().find(|client| client.window.id() == wid))
+    }
+
+    pub fn take_client(&mut self, wid: u32) -> Option<Client<B::Window>> {
+        self.monitors
+            .iter_mut()
+            .find_map(|monitor| monitor.workspace.take(wid))
+            .or_else(|| {
+                let index = self.scratchpads.iter().position(|client| client.window.id() == wid)?;
+
+                Some(self.scratchpads.remove(index))
+            })
+    }
+
+    pub fn extract_client(&mut self, wid: u32) -> Option<(usize, Client<B::Window>)> {
+        self.monitors
+            .iter_mut()
+            .enumerate()
+            .find_map(|(count, monitor)| {
+                monitor
+                    .workspace
+                    .find(wid)
+                    .map(|index| (count, monitor.workspace.remove(index)))
+            })
+    }
+
+    pub fn focused<F>(&mut self, mut f: F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnMut(usize, &mut Monitor<B::Window>) -> Result<(), Box<dyn std::error::Error>>,
+    {
+        let (x, y) = self.backend.query_pointer()?;
+
+        for (index, monitor) in self.monitors.iter_mut().enumerate() {
+            if monitor.area.contains(x, y) {
+                f(index, monitor)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn all<F>(&mut self, mut f: F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnMut(usize, &mut Monitor<B::Window>) -> Result<(), Box<dyn std::error::Error>>,
+    {
+        for (index, monitor) in self.monitors.iter_mut().enumerate() {
+            f(index, monitor)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Grab {
+    button: Button,
+    window: Window,
+    geometry: GetGeometryResponse,
+    hints: SizeHints,
+    x: u16,
+    y: u16,
+}
+
+impl Grab {
+    pub fn new(
+        button: Button,
+        window: Window,
+        geometry: GetGeometryResponse,
+        hints: SizeHints,
+        x: u16,
+        y: u16,
+    ) -> Grab {
+        Grab {
+            button,
+            window,
+            geometry,
+            hints,
+            x,
+            y,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Atoms {
+    wm_delete: Atom,
+    wm_protocols: Atom,
+    wm_state: Atom,
+    atom: Atom,
+    window: Atom,
+    net_active_window: Atom,
+    net_client_list: Atom,
+    net_client_list_stacking: Atom,
+    net_current_desktop: Atom,
+    net_number_of_desktops: Atom,
+    net_desktop_viewport: Atom,
+    net_wm_desktop: Atom,
+    net_wm_pid: Atom,
+    net_wm_strut: Atom,
+    net_wm_strut_partial: Atom,
+    net_workarea: Atom,
+    cardinal: Atom,
+    net_wm_state: Atom,
+    net_wm_states: Vec<(WmState, Atom)>,
+    net_wm_name: Atom,
+    wm_class: Atom,
+    wm_window_role: Atom,
+    string: Atom,
+    utf8_string: Atom,
+    wm_normal_hints: Atom,
+    wm_size_hints: Atom,
+    wm_transient_for: Atom,
+}
+
+impl Atoms {
+    fn net_wm_state(&self, atom: u32) -> Option<WmState> {
+        self.net_wm_states
+            .iter()
+            .find(|(_, other)| other.id() == atom)
+            .map(|(state, _)| *state)
+    }
+
+    fn net_wm_state_atom(&self, state: WmState) -> Option<Atom> {
+        self.net_wm_states
+            .iter()
+            .find(|(other, _)| *other == state)
+            .map(|(_, atom)| *atom)
+    }
+}
+
+pub struct WindowManager<B: Backend = X11> {
+    backend: B,
+    root: B::Window,
+    monitors: Monitors<B>,
+    config: Config,
+    events: EventQueue,
+    atoms: Atoms,
+    grab: Option<Grab>,
+    subscribers: Subscribers,
+    // scratchpads spawned by a toggle and the pid of their command, shown as soon as their window maps
+    pending_scratchpads: Vec<(String, u32)>,
+    // most recently focused clients first, a workspace's own history is this filtered to its clients
+    history: Vec<u32>,
+    // position in the history and window of an ongoing --focus-cycle-mru
+    cycle: Option<(usize, u32)>,
+    should_close: bool,
+}
+
+impl<B: Backend> WindowManager<B> {
+    pub fn with_backend(backend: B) -> Result<WindowManager<B>, Box<dyn std::error::Error>> {
+        let atoms = Atoms {
+            wm_delete: backend.intern_atom("WM_DELETE_WINDOW")?,
+            wm_protocols: backend.intern_atom("WM_PROTOCOLS")?,
+            wm_state: backend.intern_atom("WM_STATE")?,
+            atom: backend.intern_atom("ATOM")?,
+            window: backend.intern_atom("WINDOW")?,
+            net_active_window: backend.intern_atom("_NET_ACTIVE_WINDOW")?,
+            net_client_list: backend.intern_atom("_NET_CLIENT_LIST")?,
+            net_client_list_stacking: backend.intern_atom("_NET_CLIENT_LIST_STACKING")?,
+            net_current_desktop: backend.intern_atom("_NET_CURRENT_DESKTOP")?,
+            net_number_of_desktops: backend.intern_atom("_NET_NUMBER_OF_DESKTOPS")?,
+            net_desktop_viewport: backend.intern_atom("_NET_DESKTOP_VIEWPORT")?,
+            net_wm_desktop: backend.intern_atom("_NET_WM_DESKTOP")?,
+            net_wm_pid: backend.intern_atom("_NET_WM_PID")?,
+            net_wm_strut: backend.intern_atom("_NET_WM_STRUT")?,
+            net_wm_strut_partial: backend.intern_atom("_NET_WM_STRUT_PARTIAL")?,
+            net_workarea: backend.intern_atom("_NET_WORKAREA")?,
+            cardinal: backend.intern_atom("CARDINAL")?,
+            net_wm_state: backend.intern_atom("_NET_WM_STATE")?,
+            net_wm_states: WmState::ALL
+                .into_iter()
+                .map(|state| Ok((state, backend.intern_atom(state.name())?)))
+                .collect::<Result<Vec<(WmState, Atom)>, Box<dyn std::error::Error>>>()?,
+            net_wm_name: backend.intern_atom("_NET_WM_NAME")?,
+            wm_class: backend.intern_atom("WM_CLASS")?,
+            wm_window_role: backend.intern_atom("WM_WINDOW_ROLE")?,
+            string: backend.intern_atom("STRING")?,
+            utf8_string: backend.intern_atom("UTF8_STRING")?,
+            wm_normal_hints: backend.intern_atom("WM_NORMAL_HINTS")?,
+            wm_size_hints: backend.intern_atom("WM_SIZE_HINTS")?,
+            wm_transient_for: backend.intern_atom("WM_TRANSIENT_FOR")?,
+        };
+
+        Ok(WindowManager {
+            root: backend.root(),
+            monitors: Monitors::new(backend.clone()),
+            backend,
+            config: Config::default(),
+            events: EventQueue::new(),
+            atoms,
+            grab: None,
+            subscribers: Subscribers::new(),
+            pending_scratchpads: Vec::new(),
+            history: Vec::new(),
+            cycle: None,
+            should_close: false,
+        })
+    }
 
-        for screen in &screens {
+    fn load_monitors(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        for area in self.backend.screens()? {
             self.monitors.append(Monitor {
-                area: Area::new(screen.x, screen.y, screen.width, screen.height),
+                area,
+                reserved: Padding::default(),
+                docks: Vec::new(),
                 workspace: Workspaces::new(),
             });
         }
 
         self.update_viewport()?;
 
+        self.update_workarea()
+    }
+
+    fn set_workspaces(&mut self, count: u32) -> Result<(), Box<dyn std::error::Error>> {
+        self.monitors.all(|_, monitor| {
+            monitor.workspace.resize(count as usize);
+
+            Ok(())
+        })?;
+
+        self.root.set_property(
+            self.atoms.net_number_of_desktops,
+            self.atoms.cardinal,
+            &[count * self.monitors.monitors.len() as u32],
+        )?;
+
+        self.update_viewport()?;
+
+        self.update_workarea()
+    }
+
+    fn apply_config(&mut self, config: Config) -> Result<(), Box<dyn std::error::Error>> {
+        self.config = config;
+
+        if let Some(count) = self.config.workspaces.per_monitor {
+            self.set_workspaces(count)?;
+        }
+
+        let workspaces = &self.config.workspaces;
+
+        for monitor in self.monitors.monitors.iter_mut() {
+            for (index, workspace) in monitor.workspace.workspaces.iter_mut().enumerate() {
+                if let Some(layout) = workspaces.layout(index) {
+                    workspace.layout = layout;
+                }
+            }
+        }
+
+        self.tile()?;
+
+        self.update_borders()?;
+
+        self.subscribers.emit(Notification::Config);
+
         Ok(())
     }
 
     fn update_viewport(&self) -> Result<(), Box<dyn std::error::Error>> {
         let viewport = self.monitors.monitors.iter()
-            .flat_map(|monitor| vec![DesktopViewport::new(monitor.area.x as u32, monitor.area.y as u32); monitor.workspace.len()])
-            .collect::<Vec<DesktopViewport>>();
+            .flat_map(|monitor| [monitor.area.x as u32, monitor.area.y as u32].repeat(monitor.workspace.len()))
+            .collect::<Vec<u32>>();
 
-        self.display
-            .use_ewmh(&self.root)
-            .set_desktop_viewport(&viewport)?;
+        self.root.set_property(self.atoms.net_desktop_viewport, self.atoms.cardinal, &viewport)
+    }
 
-        Ok(())
+    fn update_workarea(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.root.set_property(
+            self.atoms.net_workarea,
+            self.atoms.cardinal,
+            &self.monitors.workarea(),
+        )
     }
 
-    fn set_supporting_ewmh(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let window = self.root.create_window(WindowArguments {
-            depth: self.root.depth(),
-            x: 0,
-            y: 0,
-            width: 1,
-            height: 1,
-            class: WindowClass::InputOutput,
-            border_width: 0,
-            visual: self.root.visual(),
-            values: ValuesBuilder::new(vec![]),
+    fn read_strut(&self, window: &B::Window) -> Result<Option<Strut>, Box<dyn std::error::Error>> {
+        let partial = window.property(self.atoms.net_wm_strut_partial, self.atoms.cardinal)?;
+
+        if partial.len() >= 12 {
+            return Ok(Some(Strut::partial(&partial)));
+        }
+
+        let strut = window.property(self.atoms.net_wm_strut, self.atoms.cardinal)?;
+
+        Ok((strut.len() >= 4).then(|| Strut::full(&strut)))
+    }
+
+    fn place_dock(&mut self, window: B::Window) -> Result<(), Box<dyn std::error::Error>> {
+        let geometry = window.geometry()?;
+        let strut = self.read_strut(&window)?;
+
+        self.monitors.place_dock(
+            Dock::new(window, strut),
+            geometry.x + geometry.width / 2,
+            geometry.y + geometry.height / 2,
+        );
+
+        self.reserve()
+    }
+
+    fn properties(&self, window: &B::Window, type_: &[EwmhWindowType]) -> Result<Properties, Box<dyn std::error::Error>> {
+        let class = window.text(self.atoms.wm_class, self.atoms.string)?;
+        let name = window.text(self.atoms.net_wm_name, self.atoms.utf8_string)?;
+        let role = window.text(self.atoms.wm_window_role, self.atoms.string)?;
+
+        // WM_CLASS holds the instance followed by the class
+        Ok(Properties {
+            instance: class.first().cloned().unwrap_or_default(),
+            class: class.get(1).cloned().unwrap_or_default(),
+            name: name.into_iter().next().unwrap_or_default(),
+            role: role.into_iter().next().unwrap_or_default(),
+            type_: type_.to_vec(),
+        })
+    }
+
+    fn manage(&mut self, window: B::Window, type_: &[EwmhWindowType]) -> Result<(), Box<dyn std::error::Error>> {
+        let actions = rules::actions(&self.config.rules, &self.properties(&window, type_)?);
+
+        let states = window.property(self.atoms.net_wm_state, self.atoms.atom)?
+            .into_iter()
+            .filter_map(|atom| self.atoms.net_wm_state(atom))
+            .collect::<Vec<WmState>>();
+
+        let hints = SizeHints::new(&window.property(self.atoms.wm_normal_hints, self.atoms.wm_size_hints)?);
+
+        // only transients of managed clients are placed relative to their parent
+        let parent = window
+            .property(self.atoms.wm_transient_for, self.atoms.window)?
+            .first()
+            .copied()
+            .filter(|parent| *parent != window.id())
+            .and_then(|parent| Some((parent, self.monitors.locate(parent)?)));
+
+        let state = match actions.placement {
+            Some(Placement::Tiled) => State::Tiled,
+            Some(Placement::Float) => State::Float,
+            _ if hints.is_fixed() || parent.is_some() || states.contains(&WmState::Sticky) => State::Float,
+            Some(Placement::Fullscreen) | None => State::from(type_),
+        };
+
+        if let Some(area) = actions.geometry {
+            window.mov_resize(area.x, area.y, area.width, area.height)?;
+        } else if let Some((parent, _)) = parent {
+            let parent = self.backend.window(parent)?.geometry()?;
+            let area = window.geometry()?;
+
+            let center = |start: u16, outer: u16, inner: u16| {
+                (start as i32 + (outer as i32 - inner as i32) / 2).max(0) as u16
+            };
+
+            window.mov_resize(
+                center(parent.x, parent.width, area.width),
+                center(parent.y, parent.height, area.height),
+                area.width,
+                area.height,
+            )?;
+        }
+
+        let mut client = Client::new(window.clone(), state).with_states(states);
+
+        client.border_width = actions.border_width;
+        client.hints = hints;
+        client.transient_for = parent.map(|(parent, _)| parent);
+
+        // without a rule naming it, a pending scratchpad only claims the window of the command it spawned
+        let pid = window.property(self.atoms.net_wm_pid, self.atoms.cardinal)?.first().copied();
+
+        let scratchpad = actions.scratchpad.clone().or_else(|| {
+            self.pending_scratchpads
+                .iter()
+                .find(|(_, child)| Some(*child) == pid)
+                .map(|(name, _)| name.clone())
+        });
+
+        let scratchpad = scratchpad.filter(|name| self.monitors.scratchpad(name).is_none());
+
+        if scratchpad.is_some() {
+            client.state = State::Float;
+            client.scratchpad = scratchpad.clone();
+        }
+
+        let (monitor, workspace) = match parent {
+            Some((_, (monitor, workspace))) => (monitor, Some(workspace)),
+            None => (self.monitors.focused_index()?.unwrap_or_default(), None),
+        };
+
+        let monitor = match actions.monitor {
+            Some(monitor) if (monitor as usize) <= self.monitors.monitors.len() => monitor as usize - 1,
+            _ => monitor,
+        };
+
+        let Some(target) = self.monitors.monitors.get_mut(monitor) else {
+            return Ok(());
+        };
+
+        let workspace = match actions.workspace {
+            Some(workspace) if (workspace as usize) <= target.workspace.len() => Some(workspace as usize - 1),
+            _ => workspace.filter(|workspace| *workspace < target.workspace.len()),
+        };
+
+        match workspace {
+            Some(workspace) => target.workspace.insert_at(workspace, client),
+            None => target.workspace.insert(client),
+        }
+
+        self.subscribers.emit(Notification::Map(window.id()));
+
+        if let Some(name) = scratchpad {
+            let pending = self.pending_scratchpads.iter().position(|(other, _)| *other == name);
+
+            if let Some(index) = pending {
+                self.pending_scratchpads.remove(index);
+
+                return self.show_scratchpad(window.id(), monitor);
+            }
+
+            return self.hide_scratchpad(window.id());
+        }
+
+        self.tile()?;
+
+        // a new transient starts out above its parent
+        if parent.is_some() {
+            window.raise()?;
+        }
+
+        if actions.placement == Some(Placement::Fullscreen) {
+            self.set_wm_state(window.id(), WmState::Fullscreen, |_| true)?;
+        }
+
+        let visible = self.monitors.client_mut(window.id()).is_some_and(|client| client.mapped);
+
+        if visible && actions.focus.unwrap_or(true) {
+            window.focus()?;
+
+            self.set_border(&window)
+        } else {
+            self.update_borders()
+        }
+    }
+
+    fn show_scratchpad(&mut self, wid: u32, index: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(client) = self.monitors.take_client(wid) else {
+            return Ok(());
+        };
+
+        let area = self.monitors.monitors[index].workarea();
+        let geometry = client.window.geometry()?;
+
+        client.window.mov_resize(
+            area.x + area.width.saturating_sub(geometry.width) / 2,
+            area.y + area.height.saturating_sub(geometry.height) / 2,
+            geometry.width,
+            geometry.height,
+        )?;
+
+        let window = client.window.clone();
+
+        self.monitors.monitors[index].workspace.insert(client);
+
+        self.tile()?;
+
+        window.raise()?;
+        window.focus()?;
+
+        self.set_border(&window)
+    }
+
+    fn hide_scratchpad(&mut self, wid: u32) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(mut client) = self.monitors.take_client(wid) else {
+            return Ok(());
+        };
+
+        client.hide()?;
+
+        client.window.set_property(self.atoms.wm_state, self.atoms.wm_state, &[WM_STATE_ICONIC, 0])?;
+
+        self.monitors.scratchpads.push(client);
+
+        self.tile()
+    }
+
+    fn toggle_scratchpad(&mut self, name: &str) -> Result<Reply, Box<dyn std::error::Error>> {
+        let Some(index) = self.monitors.focused_index()? else {
+            return Ok(Reply::error(Status::Failed, "pointer is not on any monitor"));
+        };
+
+        match self.monitors.scratchpad(name) {
+            Some(wid) if self.monitors.monitors[index].workspace.find(wid).is_some() => {
+                self.hide_scratchpad(wid)?;
+            }
+            Some(wid) => self.show_scratchpad(wid, index)?,
+            None => {
+                let Some((_, command)) = self.config.scratchpads.iter().find(|(other, _)| other == name) else {
+                    return Ok(Reply::error(
+                        Status::InvalidArgument,
+                        format!("scratchpad {} does not exist and has no command", name),
+                    ));
+                };
+
+                let pid = startup::spawn(command)?;
+
+                self.pending_scratchpads.push((name.to_string(), pid));
+            }
+        }
+
+        Ok(Reply::ok())
+    }
+
+    fn reserve(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let geometry = self.root.geometry()?;
+
+        self.monitors.reserve(Area::new(0, 0, geometry.width, geometry.height));
+
+        self.update_workarea()?;
+
+        self.tile()
+    }
+
+    fn tile(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.monitors.all(|_, monitor| {
+            monitor.tile(&self.config)
+        })?;
+
+        self.update_client_list()
+    }
+
+    fn update_client_list(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.root.set_property(
+            self.atoms.net_client_list,
+            self.atoms.window,
+            &self.monitors.clients(),
+        )?;
+
+        self.root.set_property(
+            self.atoms.net_client_list_stacking,
+            self.atoms.window,
+            &self.monitors.stacking(),
+        )?;
+
+        for (client, desktop) in self.monitors.desktops() {
+            let desktop = if client.has(WmState::Sticky) { ALL_DESKTOPS } else { desktop };
+
+            client.window.set_property(self.atoms.net_wm_desktop, self.atoms.cardinal, &[desktop])?;
+
+            client.window.set_property(self.atoms.wm_state, self.atoms.wm_state, &[client.wm_state(), 0])?;
+        }
+
+        Ok(())
+    }
+
+    fn update_active_window(&self, wid: u32) -> Result<(), Box<dyn std::error::Error>> {
+        let active = if self.monitors.clients().contains(&wid) { wid } else { 0 };
+
+        self.root.set_property(self.atoms.net_active_window, self.atoms.window, &[active])
+    }
+
+    fn switch_workspace(&mut self, index: usize, workspace: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let monitor = &mut self.monitors.monitors[index];
+
+        monitor.workspace.set_current(workspace);
+
+        self.root.set_property(
+            self.atoms.net_current_desktop,
+            self.atoms.cardinal,
+            &[(monitor.workspace.len() * index + workspace) as u32],
+        )?;
+
+        self.subscribers.emit(Notification::Workspace {
+            monitor: index as u32,
+            workspace: workspace as u32 + 1,
+        });
+
+        monitor.tile(&self.config)?;
+
+        self.update_client_list()
+    }
+
+    fn remember_focus(&mut self, wid: u32) {
+        // cycling leaves the history alone until focus moves some other way
+        match self.cycle {
+            Some((_, target)) if target == wid => return,
+            Some((_, target)) => {
+                self.history.retain(|other| *other != target);
+                self.history.insert(0, target);
+            }
+            None => {}
+        }
+
+        self.cycle = None;
+
+        self.history.retain(|other| *other != wid);
+        self.history.insert(0, wid);
+    }
+
+    // stops managing a client, focus goes back to the one used before it on its workspace
+    fn unmanage(&mut self, wid: u32) -> Result<Option<Client<B::Window>>, Box<dyn std::error::Error>> {
+        let focused = self.cycle.map(|(_, target)| target).or(self.history.first().copied()) == Some(wid);
+        let location = self.monitors.locate(wid);
+
+        self.history.retain(|other| *other != wid);
+        self.cycle = None;
+
+        let client = self.monitors.take_client(wid);
+
+        let Some((index, workspace)) = location.filter(|_| focused) else {
+            return Ok(client);
+        };
+
+        if self.monitors.monitors[index].workspace.current != workspace {
+            return Ok(client);
+        }
+
+        for previous in self.history.clone() {
+            if self.monitors.locate(previous) != Some((index, workspace)) {
+                continue;
+            }
+
+            if let Some(previous) = self
+                .monitors
+                .client_mut(previous)
+                .filter(|client| client.in_scope(FocusScope::All))
+            {
+                previous.window.focus()?;
+
+                break;
+            }
+        }
+
+        Ok(client)
+    }
+
+    // the history without clients that are not on a monitor, e.g. hidden scratchpads
+    fn focus_history(&self) -> Vec<u32> {
+        self.history
+            .iter()
+            .copied()
+            .filter(|wid| self.monitors.locate(*wid).is_some())
+            .collect()
+    }
+
+    fn activate(&mut self, wid: u32) -> Result<bool, Box<dyn std::error::Error>> {
+        let Some((index, workspace)) = self.monitors.locate(wid) else {
+            return Ok(false);
+        };
+
+        if self.monitors.monitors[index].workspace.current != workspace {
+            self.switch_workspace(index, workspace)?;
+        }
+
+        self.set_wm_state(wid, WmState::Hidden, |_| false)?;
+
+        let window = self.backend.window(wid)?;
+        let geometry = window.geometry()?;
+
+        // requests are routed to the monitor under the pointer, so it has to follow the focus
+        self.backend.warp_pointer(
+            geometry.x + geometry.width / 2,
+            geometry.y + geometry.height / 2,
+        )?;
+
+        window.raise()?;
+
+        window.focus()?;
+
+        Ok(true)
+    }
+
+    fn focused_client<F>(&mut self, mut f: F) -> Result<bool, Box<dyn std::error::Error>>
+    where
+        F: FnMut(&mut Client<B::Window>) -> Result<(), Box<dyn std::error::Error>>,
+    {
+        let focus = self.backend.input_focus()?;
+        let mut found = false;
+
+        self.monitors.focused(|_, monitor| {
+            if let Some(index) = monitor.workspace.find(focus) {
+                f(&mut monitor.workspace.workspaces[monitor.workspace.current].clients[index])?;
+
+                found = true;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(found)
+    }
+
+    fn monitor_circulate(&mut self) -> Result<Reply, Box<dyn std::error::Error>> {
+        let focus = self.backend.input_focus()?;
+
+        let Some((count, client)) = self.monitors.extract_client(focus) else {
+            return Ok(no_focus());
+        };
+
+        let index = (count + 1) % self.monitors.monitors.len();
+
+        self.monitors.monitors[index].workspace.insert(client);
+
+        let workspace = self.monitors.monitors[index].workspace.current;
+
+        for transient in self.monitors.transients(focus) {
+            self.monitors.move_client(transient, index, workspace);
+        }
+
+        self.subscribers.emit(Notification::Monitor {
+            monitor: index as u32,
+            window: focus,
+        });
+
+        self.tile()?;
+
+        Ok(Reply::ok())
+    }
+
+    // the nearest client in `direction` on the client's monitor, else the adjacent monitor and its nearest client
+    fn neighbour(
+        &self,
+        wid: u32,
+        direction: Direction,
+        tiled: bool,
+    ) -> Result<Neighbour, Box<dyn std::error::Error>> {
+        let Some(index) = self
+            .monitors
+            .monitors
+            .iter()
+            .position(|monitor| monitor.workspace.find(wid).is_some())
+        else {
+            return Ok(Neighbour::Edge);
+        };
+
+        let from = self.backend.window(wid)?.geometry()?;
+
+        let nearest = |index: usize| -> Result<Option<u32>, Box<dyn std::error::Error>> {
+            Ok(self.monitors.monitors[index]
+                .workspace
+                .geometries(tiled)?
+                .into_iter()
+                .filter(|(other, _)| *other != wid)
+                .filter_map(|(other, area)| Some((direction.distance(from, area)?, other)))
+                .min()
+                .map(|(_, other)| other))
+        };
+
+        if let Some(window) = nearest(index)? {
+            return Ok(Neighbour::Client { monitor: index, window });
+        }
+
+        let area = self.monitors.monitors[index].area;
+
+        let adjacent = self
+            .monitors
+            .monitors
+            .iter()
+            .enumerate()
+            .filter_map(|(other, monitor)| Some((direction.distance(area, monitor.area)?, other)))
+            .min()
+            .map(|(_, other)| other);
+
+        let Some(monitor) = adjacent else {
+            return Ok(Neighbour::Edge);
+        };
+
+        Ok(match nearest(monitor)? {
+            Some(window) => Neighbour::Client { monitor, window },
+            None => Neighbour::Monitor(monitor),
+        })
+    }
+
+    fn focus_dir(&mut self, direction: Direction) -> Result<Reply, Box<dyn std::error::Error>> {
+        let focus = self.backend.input_focus()?;
+
+        let Some(origin) = self.monitors.locate(focus).map(|(index, _)| index) else {
+            return Ok(no_focus());
+        };
+
+        match self.neighbour(focus, direction, false)? {
+            Neighbour::Client { monitor, window } if monitor == origin => {
+                self.backend.window(window)?.focus()?
+            }
+            Neighbour::Client { window, .. } => {
+                self.activate(window)?;
+            }
+            Neighbour::Monitor(monitor) => {
+                let area = self.monitors.monitors[monitor].area;
+
+                self.backend
+                    .warp_pointer(area.x + area.width / 2, area.y + area.height / 2)?;
+            }
+            Neighbour::Edge => {}
+        }
+
+        Ok(Reply::ok())
+    }
+
+    fn swap_dir(&mut self, direction: Direction) -> Result<Reply, Box<dyn std::error::Error>> {
+        let focus = self.backend.input_focus()?;
+
+        let Some((origin, _)) = self.monitors.locate(focus) else {
+            return Ok(no_focus());
+        };
+
+        if self.monitors.client_mut(focus).is_some_and(|client| !client.is_tiled()) {
+            return Ok(Reply::ok());
+        }
+
+        match self.neighbour(focus, direction, true)? {
+            Neighbour::Client { monitor, window } => {
+                self.monitors.exchange(focus, window);
+
+                if monitor != origin {
+                    for (monitor, window) in [(monitor, focus), (origin, window)] {
+                        self.subscribers.emit(Notification::Monitor {
+                            monitor: monitor as u32,
+                            window,
+                        });
+                    }
+                }
+            }
+            Neighbour::Monitor(monitor) => {
+                let workspace = self.monitors.monitors[monitor].workspace.current;
+
+                self.monitors.move_client(focus, monitor, workspace);
+
+                self.subscribers.emit(Notification::Monitor {
+                    monitor: monitor as u32,
+                    window: focus,
+                });
+            }
+            Neighbour::Edge => return Ok(Reply::ok()),
+        }
+
+        self.tile()?;
+
+        if self.monitors.locate(focus).is_some_and(|(index, _)| index != origin) {
+            self.activate(focus)?;
+        }
+
+        Ok(Reply::ok())
+    }
+
+    fn mov_resize_focused<F>(&mut self, transform: F) -> Result<Reply, Box<dyn std::error::Error>>
+    where
+        F: Fn(u16, u16, u16, u16) -> (u16, u16, u16, u16),
+    {
+        let mut floating = false;
+
+        let found = self.focused_client(|client| {
+            if client.state == State::Float {
+                let geometry = client.window.geometry()?;
+
+                let (x, y, width, height) =
+                    transform(geometry.x, geometry.y, geometry.width, geometry.height);
+
+                let (width, height) = client.hints.constrain(width, height, true);
+
+                client.window.mov_resize(x, y, width, height)?;
+
+                floating = true;
+            }
+
+            Ok(())
         })?;
 
-        let ewmh = self.display.use_ewmh(&window);
+        match (found, floating) {
+            (false, _) => Ok(no_focus()),
+            (true, false) => Ok(Reply::error(
+                Status::InvalidArgument,
+                "focused client is not floating",
+            )),
+            (true, true) => Ok(Reply::ok()),
+        }
+    }
+
+    fn set_wm_state<F>(&mut self, wid: u32, state: WmState, f: F) -> Result<bool, Box<dyn std::error::Error>>
+    where
+        F: Fn(bool) -> bool,
+    {
+        let Some(client) = self.monitors.client_mut(wid) else {
+            return Ok(false);
+        };
+
+        let enabled = f(client.has(state));
+
+        if enabled == client.has(state) {
+            return Ok(true);
+        }
+
+        if enabled && !client.overrides_geometry() && client.state == State::Float {
+            client.geometry = Some(client.window.geometry()?);
+        }
+
+        client.set(state, enabled);
+
+        // sticky clients float above every workspace
+        if state == WmState::Sticky && enabled && client.state == State::Tiled {
+            client.state = State::Float;
+        }
+
+        if !client.overrides_geometry() {
+            if let Some(area) = client.geometry.take() {
+                client
+                    .window
+                    .mov_resize(area.x, area.y, area.width, area.height)?;
+            }
+        }
+
+        let states = client
+            .states
+            .iter()
+            .filter_map(|state| self.atoms.net_wm_state_atom(*state))
+            .map(|atom| atom.id())
+            .collect::<Vec<u32>>();
+
+        client.window.set_property(self.atoms.net_wm_state, self.atoms.atom, &states)?;
+
+        if let Some((index, workspace)) = self.monitors.locate(wid).filter(|_| state == WmState::Sticky && enabled) {
+            let current = self.monitors.monitors[index].workspace.current;
+
+            if workspace != current {
+                self.monitors.move_client(wid, index, current);
+            }
+        }
+
+        self.tile()?;
+
+        self.update_borders()?;
+
+        Ok(true)
+    }
+
+    fn update_borders(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let focus = self.backend.input_focus()?;
+
+        if focus != self.root.id() && focus > 1 {
+            let window = self.backend.window(focus)?;
+
+            self.set_border(&window)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_border(&mut self, window: &B::Window) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.backend.window_type(window)?.contains(&EwmhWindowType::Dock) {
+            let borders = self.config.windows.borders;
+
+            self.monitors.all(|_, monitor| {
+                monitor.workspace.map_clients(|client| {
+                    client.window.set_border_width(client.border_width(borders.width))?;
+
+                    client.window.set_border_pixel(borders.normal)
+                })
+            })?;
+
+            window.set_border_pixel(borders.focused)?;
+        }
+
+        Ok(())
+    }
+
+    fn query(&mut self, request: Request) -> Result<Reply, Box<dyn std::error::Error>> {
+        match request {
+            Request::QueryFocused => {
+                let focus = self.backend.input_focus()?;
+
+                Ok(Reply::Focused(
+                    self.monitors
+                        .clients()
+                        .contains(&focus)
+                        .then_some(focus),
+                ))
+            }
+            Request::QueryWorkspaces => Ok(Reply::Workspaces(
+                self.monitors
+                    .monitors
+                    .iter()
+                    .map(|monitor| monitor.workspace.current as u32 + 1)
+                    .collect(),
+            )),
+            Request::QueryClients => Ok(Reply::Clients(self.monitors.clients())),
+            Request::QueryConfig => {
+                let mut entries = self.config.entries();
+
+                entries.push((
+                    String::from("workspaces-per-monitor"),
+                    self.monitors
+                        .monitors
+                        .first()
+                        .map(|monitor| monitor.workspace.len() as u32)
+                        .unwrap_or_default(),
+                ));
+
+                Ok(Reply::Config(entries))
+            }
+            _ => Err(format!("not a query: {:?}", request).into()),
+        }
+    }
+
+    fn handle_sequence(&mut self, sequence: Sequence) -> Result<Reply, Box<dyn std::error::Error>> {
+            match sequence.request {
+                Request::Workspace => {
+                    let Some(index) = self.monitors.focused_index()? else {
+                        return Ok(Reply::error(Status::Failed, "pointer is not on any monitor"));
+                    };
+
+                    let len = self.monitors.monitors[index].workspace.len();
+
+                    if !(1..=len as u32).contains(&sequence.value()) {
+                        return Ok(Reply::error(
+                            Status::InvalidArgument,
+                            format!("workspace {} is out of range 1-{}", sequence.value(), len),
+                        ));
+                    }
+
+                    self.switch_workspace(index, sequence.value() as usize - 1)?;
+                }
+                Request::SendToWorkspace => {
+                    let focus = self.backend.input_focus()?;
+
+                    let Some(index) = self.monitors.focused_index()? else {
+                        return Ok(Reply::error(Status::Failed, "pointer is not on any monitor"));
+                    };
+
+                    let len = self.monitors.monitors[index].workspace.len();
+
+                    if !(1..=len as u32).contains(&sequence.value()) {
+                        return Ok(Reply::error(
+                            Status::InvalidArgument,
+                            format!("workspace {} is out of range 1-{}", sequence.value(), len),
+                        ));
+                    }
+
+                    if !self.monitors.move_client(focus, index, sequence.value() as usize - 1) {
+                        return Ok(no_focus());
+                    }
+
+                    self.tile()?;
+
+                    if sequence.integer(1).unwrap_or_default() != 0 {
+                        self.activate(focus)?;
+                    }
+                }
+                Request::Kill => {
+                    if !self.focused_client(|client| client.window.kill())? {
+                        return Ok(no_focus());
+                    }
+                }
+                Request::Close => {
+                    let atoms = self.atoms.clone();
+
+                    let found = self.focused_client(|client| {
+                        client.window.close(atoms.wm_protocols, atoms.wm_delete)
+                    })?;
+
+                    if !found {
+                        return Ok(no_focus());
+                    }
+                }
+                Request::FocusUp | Request::FocusDown | Request::FocusMaster => {
+                    let focus = self.backend.input_focus()?;
+                    let mut found = false;
+
+                    let scope = self.config.windows.focus_scope;
+
+                    self.monitors.focused(|_, monitor| {
+                        found |= match sequence.request {
+                            Request::FocusUp => monitor.workspace.change_focus(focus, scope, |position, count| {
+                                position.map_or(count - 1, |position| position + count - 1)
+                            }),
+                            Request::FocusDown => monitor.workspace.change_focus(focus, scope, |position, _| {
+                                position.map_or(0, |position| position + 1)
+                            }),
+                            Request::FocusMaster => {
+                                monitor.workspace.change_focus(focus, FocusScope::Tiled, |_, _| 0)
+                            }
+                            _ => Ok(false),
+                        }?;
+
+                        Ok(())
+                    })?;
+
+                    if !found {
+                        return Ok(no_focus());
+                    }
+                }
+                Request::FocusFloatToggle => {
+                    let focus = self.backend.input_focus()?;
+                    let mut found = false;
+
+                    self.monitors.focused(|_, monitor| {
+                        let scope = match monitor.workspace.is_float(focus) {
+                            true => FocusScope::Tiled,
+                            false => FocusScope::Floating,
+                        };
+
+                        found |= monitor.workspace.change_focus(focus, scope, |_, _| 0)?;
+
+                        Ok(())
+                    })?;
+
+                    if !found {
+                        return Ok(no_focus());
+                    }
+                }
+                Request::FocusLast => {
+                    let focus = self.backend.input_focus()?;
+
+                    let Some(last) = self.focus_history().into_iter().find(|wid| *wid != focus) else {
+                        return Ok(no_focus());
+                    };
+
+                    self.activate(last)?;
+                }
+                Request::FocusCycleMru => {
+                    let history = self.focus_history();
+
+                    if history.len() < 2 {
+                        return Ok(no_focus());
+                    }
+
+                    let position = self
+                        .cycle
+                        .map_or(1, |(position, _)| (position + 1) % history.len());
+
+                    self.cycle = Some((position, history[position]));
+
+                    self.activate(history[position])?;
+                }
+                Request::FocusScope => match FocusScope::try_from(sequence.string().unwrap_or_default()) {
+                    Ok(scope) => {
+                        self.config.windows.focus_scope = scope;
+
+                        self.subscribers.emit(Notification::Config);
+                    }
+                    Err(err) => return Ok(Reply::error(Status::InvalidArgument, err)),
+                },
+                Request::SwapUp | Request::SwapDown | Request::SwapMaster => {
+                    let focus = self.backend.input_focus()?;
+                    let mut found = false;
+
+                    self.monitors.focused(|_, monitor| {
+                        found |= monitor.workspace.swap(focus, |position, count| match sequence.request {
+                            Request::SwapUp => position.checked_sub(1),
+                            Request::SwapDown => (position + 1 < count).then_some(position + 1),
+                            _ if position == 0 => (count > 1).then_some(1),
+                            _ => Some(0),
+                        });
+
+                        Ok(())
+                    })?;
+
+                    if !found {
+                        return Ok(no_focus());
+                    }
+
+                    self.tile()?;
+                }
+                Request::FocusDir | Request::SwapDir => {
+                    let direction = match Direction::try_from(sequence.string().unwrap_or_default()) {
+                        Ok(direction) => direction,
+                        Err(err) => return Ok(Reply::error(Status::InvalidArgument, err)),
+                    };
+
+                    return match sequence.request {
+                        Request::FocusDir => self.focus_dir(direction),
+                        _ => self.swap_dir(direction),
+                    };
+                }
+                Request::RotateStack => {
+                    self.monitors.focused(|_, monitor| {
+                        monitor.workspace.rotate();
+
+                        Ok(())
+                    })?;
+
+                    self.tile()?;
+                }
+                Request::PaddingTop
+                | Request::PaddingBottom
+                | Request::PaddingLeft
+                | Request::PaddingRight
+                | Request::WindowGaps => {
+                    match sequence.request {
+                        Request::PaddingTop => self.config.padding.top = sequence.value() as u16,
+                        Request::PaddingBottom => {
+                            self.config.padding.bottom = sequence.value() as u16
+                        }
+                        Request::PaddingLeft => self.config.padding.left = sequence.value() as u16,
+                        Request::PaddingRight => self.config.padding.right = sequence.value() as u16,
+                        Request::WindowGaps => self.config.windows.gaps = sequence.value() as u16,
+                        _ => unreachable!(),
+                    }
+
+                    self.tile()?;
+
+                    self.subscribers.emit(Notification::Config);
+                }
+                Request::FocusedBorder | Request::NormalBorder | Request::BorderWidth => {
+                    match sequence.request {
+                        Request::FocusedBorder => {
+                            self.config.windows.borders.focused = sequence.value()
+                        }
+                        Request::NormalBorder => {
+                            self.config.windows.borders.normal = sequence.value()
+                        }
+                        Request::BorderWidth => {
+                            self.config.windows.borders.width = sequence.value() as u16
+                        }
+                        _ => unreachable!(),
+                    }
+
+                    self.update_borders()?;
+
+                    self.subscribers.emit(Notification::Config);
+                }
+                Request::FloatToggle => {
+                    let found = self.focused_client(|client| {
+                        if client.state == State::Float {
+                            client.state = State::Tiled;
+                        } else if client.state != State::Dock {
+                            client.state = State::Float;
+                        }
+
+                        Ok(())
+                    })?;
+
+                    if !found {
+                        return Ok(no_focus());
+                    }
+
+                    self.tile()?;
+                }
+                Request::ToggleDocks => {
+                    self.monitors.toggle_docks()?;
+
+                    self.reserve()?;
+                }
+                Request::FullscreenToggle => {
+                    let focus = self.backend.input_focus()?;
+
+                    if !self.set_wm_state(focus, WmState::Fullscreen, |fullscreen| !fullscreen)? {
+                        return Ok(no_focus());
+                    }
+                }
+                Request::FloatRight => return self.mov_resize_focused(|x, y, width, height| {
+                    (x + sequence.value() as u16, y, width, height)
+                }),
+                Request::FloatLeft => return self.mov_resize_focused(|x, y, width, height| {
+                    (x - (sequence.value() as u16).min(x), y, width, height)
+                }),
+                Request::FloatUp => return self.mov_resize_focused(|x, y, width, height| {
+                    (x, y - (sequence.value() as u16).min(y), width, height)
+                }),
+                Request::FloatDown => return self.mov_resize_focused(|x, y, width, height| {
+                    (x, y + sequence.value() as u16, width, height)
+                }),
+                Request::ResizeRight => return self.mov_resize_focused(|x, y, width, height| {
+                    (x, y, width + sequence.value() as u16, height)
+                }),
+                Request::ResizeLeft => return self.mov_resize_focused(|x, y, width, height| {
+                    (x, y, width - (sequence.value() as u16).min(width), height)
+                }),
+                Request::ResizeUp => return self.mov_resize_focused(|x, y, width, height| {
+                    (x, y, width, height - (sequence.value() as u16).min(height))
+                }),
+                Request::ResizeDown => return self.mov_resize_focused(|x, y, width, height| {
+                    (x, y, width, height + sequence.value() as u16)
+                }),
+                Request::EnableMouse | Request::DisableMouse => {
+                    self.config.windows.mouse_movement = sequence.request == Request::EnableMouse;
+
+                    self.subscribers.emit(Notification::Config);
+                }
+                Request::WorkspacePerMonitor => {
+                    if sequence.value() == 0 {
+                        return Ok(Reply::error(
+                            Status::InvalidArgument,
+                            "workspaces per monitor must be at least 1",
+                        ));
+                    }
+
+                    self.set_workspaces(sequence.value())?;
+
+                    self.subscribers.emit(Notification::Config);
+                }
+                Request::ScratchpadAdd => {
+                    let name = sequence.string().unwrap_or_default().to_string();
+
+                    if name.is_empty() {
+                        return Ok(Reply::error(Status::InvalidArgument, "scratchpads need a name"));
+                    }
+
+                    if self.monitors.scratchpad(&name).is_some() {
+                        return Ok(Reply::error(
+                            Status::InvalidArgument,
+                            format!("scratchpad {} already exists", name),
+                        ));
+                    }
+
+                    let focus = self.backend.input_focus()?;
+
+                    let found = self.focused_client(|client| {
+                        client.state = State::Float;
+                        client.scratchpad = Some(name.clone());
+
+                        Ok(())
+                    })?;
+
+                    if !found {
+                        return Ok(no_focus());
+                    }
+
+                    self.hide_scratchpad(focus)?;
+                }
+                Request::ScratchpadToggle => {
+                    return self.toggle_scratchpad(sequence.string().unwrap_or_default());
+                }
+                Request::RuleAdd => match Rule::parse(sequence.string().unwrap_or_default()) {
+                    Ok(rule) => self.config.rules.push(rule),
+                    Err(err) => return Ok(Reply::error(Status::InvalidArgument, err)),
+                },
+                Request::RuleRemove => {
+                    if !(1..=self.config.rules.len() as u32).contains(&sequence.value()) {
+                        return Ok(Reply::error(
+                            Status::InvalidArgument,
+                            format!("rule {} does not exist", sequence.value()),
+                        ));
+                    }
+
+                    self.config.rules.remove(sequence.value() as usize - 1);
+                }
+                Request::RuleList => {
+                    return Ok(Reply::Rules(self.config.rules.iter().map(|rule| rule.to_string()).collect()));
+                }
+                Request::Reload => match Config::load() {
+                    Ok(config) => self.apply_config(config)?,
+                    Err(err) => return Ok(Reply::error(Status::Failed, err)),
+                },
+                Request::Layout | Request::LayoutNext => {
+                    let layout = match sequence.request {
+                        Request::Layout => match Layout::try_from(sequence.string().unwrap_or_default()) {
+                            Ok(layout) => Some(layout),
+                            Err(err) => return Ok(Reply::error(Status::InvalidArgument, err)),
+                        },
+                        _ => None,
+                    };
+
+                    self.monitors.focused(|_, monitor| {
+                        monitor
+                            .workspace
+                            .set_layout(layout.unwrap_or_else(|| monitor.workspace.layout().next()));
+
+                        monitor.tile(&self.config)
+                    })?;
+                }
+                Request::MasterRatio
+                | Request::MasterRatioInc
+                | Request::MasterRatioDec
+                | Request::NMaster => {
+                    if sequence.request == Request::MasterRatio
+                        && !(Master::MIN_RATIO..=Master::MAX_RATIO).contains(&sequence.value())
+                    {
+                        return Ok(Reply::error(
+                            Status::InvalidArgument,
+                            format!(
+                                "master ratio must be between {} and {}",
+                                Master::MIN_RATIO,
+                                Master::MAX_RATIO
+                            ),
+                        ));
+                    }
+
+                    self.monitors.focused(|_, monitor| {
+                        let master = monitor.workspace.master();
+
+                        monitor.workspace.set_master(match sequence.request {
+                            Request::MasterRatio => master.with_ratio(sequence.value()),
+                            Request::MasterRatioInc => {
+                                master.with_ratio(master.ratio.saturating_add(sequence.value()))
+                            }
+                            Request::MasterRatioDec => {
+                                master.with_ratio(master.ratio.saturating_sub(sequence.value()))
+                            }
+                            _ => Master {
+                                ratio: master.ratio,
+                                count: sequence.value() as usize,
+                            },
+                        });
+
+                        monitor.tile(&self.config)
+                    })?;
+                }
+                Request::MonitorCirculate => return self.monitor_circulate(),
+                Request::Quit => self.should_close = true,
+                Request::QueryFocused
+                | Request::QueryWorkspaces
+                | Request::QueryClients
+                | Request::QueryConfig => return self.query(sequence.request),
+                Request::Subscribe | Request::Unknown => {
+                    return Ok(Reply::error(Status::Unsupported, "unknown request"));
+                }
+            }
+
+        Ok(Reply::ok())
+    }
+}
+
+impl WindowManager {
+    pub fn new() -> Result<WindowManager, Box<dyn std::error::Error>> {
+        WindowManager::with_backend(X11::open()?)
+    }
+
+    fn setup(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.root.select_input(&[
+            EventMask::SubstructureNotify,
+            EventMask::SubstructureRedirect,
+            EventMask::EnterWindow,
+            EventMask::FocusChange,
+        ])?;
+
+        for button in [Button::Button1, Button::Button3] {
+            self.root.grab_button(
+                button,
+                vec![KeyMask::Mod4],
+                vec![
+                    EventMask::ButtonPress,
+                    EventMask::ButtonRelease,
+                    EventMask::ButtonMotion,
+                ],
+                Cursor::Nop,
+                PointerMode::Asynchronous,
+                KeyboardMode::Asynchronous,
+                true,
+                0,
+            )?;
+        }
+
+        server::listen(self.events.clone())?;
+
+        self.set_supporting_ewmh()?;
+
+        self.load_monitors()?;
+
+        match Config::load() {
+            Ok(config) => self.apply_config(config)?,
+            Err(err) => log::write(format!("failed to load config: {}\n", err), Severity::Error)?,
+        }
+
+        startup::startup()?;
+
+        Ok(())
+    }
+
+    fn set_supporting_ewmh(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let window = self.root.create_window(WindowArguments {
+            depth: self.root.depth(),
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+            class: WindowClass::InputOutput,
+            border_width: 0,
+            visual: self.root.visual(),
+            values: ValuesBuilder::new(vec![]),
+        })?;
+
+        let ewmh = self.backend.display.use_ewmh(&window);
+
+        ewmh.set_supporting_wm_check(window.id())?;
+
+        ewmh.set_wm_name("yaxi")?;
+
+        let root = self.backend.display.use_ewmh(&self.root);
+
+        root.set_supporting_wm_check(window.id())?;
+
+        let mut supported = vec![
+            self.backend.display.intern_atom("WM_PROTOCOLS", false)?,
+            self.backend.display.intern_atom("WM_DELETE_WINDOW", false)?,
+            self.atoms.net_active_window,
+            self.atoms.net_client_list,
+            self.atoms.net_client_list_stacking,
+            self.atoms.net_wm_desktop,
+            self.atoms.net_wm_strut,
+            self.atoms.net_wm_strut_partial,
+            self.atoms.net_workarea,
+            self.atoms.net_number_of_desktops,
+            self.atoms.net_current_desktop,
+            self.atoms.net_desktop_viewport,
+            self.backend.display.intern_atom("_NET_WM_WINDOW_TYPE", false)?,
+            self.backend.display.intern_atom("_NET_WM_WINDOW_TYPE_DESKTOP", false)?,
+            self.backend.display.intern_atom("_NET_WM_WINDOW_TYPE_DOCK", false)?,
+            self.backend.display.intern_atom("_NET_WM_WINDOW_TYPE_TOOLBAR", false)?,
+            self.backend.display.intern_atom("_NET_WM_WINDOW_TYPE_MENU", false)?,
+            self.backend.display.intern_atom("_NET_WM_WINDOW_TYPE_UTILITY", false)?,
+            self.backend.display.intern_atom("_NET_WM_WINDOW_TYPE_SPLASH", false)?,
+            self.backend.display.intern_atom("_NET_WM_WINDOW_TYPE_DIALOG", false)?,
+            self.backend.display.intern_atom("_NET_WM_WINDOW_TYPE_NORMAL", false)?,
+            self.atoms.net_wm_state,
+        ];
+
+        supported.extend(self.atoms.net_wm_states.iter().map(|(_, atom)| *atom));
+
+        root.set_supported(&supported)?;
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<(), Box<dyn std::error::Error>> {
+        match event {
+            Event::MapRequest { window, .. } => {
+                log::write(format!("map request: {}\n", window), Severity::Info)?;
+
+                let window = self.backend.window(window)?;
+                let type_ = self.backend.window_type(&window)?;
+
+                // docks update their struts and clients their size hints
+                window.select_input(&[
+                    EventMask::SubstructureNotify,
+                    EventMask::SubstructureRedirect,
+                    EventMask::EnterWindow,
+                    EventMask::FocusChange,
+                    EventMask::PropertyChange,
+                ])?;
+
+                if type_.contains(&EwmhWindowType::Dock) {
+                    if !self.monitors.docks_hidden {
+                        window.map(WindowKind::Window)?;
+                    }
+
+                    self.place_dock(window)?;
+                } else if self.monitors.client_mut(window.id()).is_none() {
+                    self.manage(window, &type_)?;
+                }
+            }
+            Event::UnmapNotify { window, .. } => {
+                log::write(format!("unmap notify: {}\n", window), Severity::Info)?;
+
+                // hidden docks were unmapped by us and stay registered
+                if !self.monitors.docks_hidden && self.monitors.remove_dock(window).is_some() {
+                    self.reserve()?;
+                }
+
+                if self
+                    .monitors
+                    .client_mut(window)
+                    .is_some_and(|client| !client.expects_unmap())
+                {
+                    if let Some(client) = self.unmanage(window)? {
+                        client.window.set_property(
+                            self.atoms.wm_state,
+                            self.atoms.wm_state,
+                            &[WM_STATE_WITHDRAWN, 0],
+                        )?;
+                    }
+
+                    self.subscribers.emit(Notification::Unmap(window));
+
+                    self.tile()?;
+                }
+            }
+            Event::EnterNotify { window, .. } => {
+                log::write(format!("enter notify: {}\n", window), Severity::Info)?;
+
+                if window != self.root.id() && window > 1 {
+                    let window = self.backend.window(window)?;
+                    if !self.backend.window_type(&window)?.contains(&EwmhWindowType::Dock) {
+                        window.set_input_focus(RevertTo::Parent)?;
+                    }
+                }
+            }
+            Event::FocusIn { window, .. } => {
+                log::write(format!("focus in: {}\n", window), Severity::Info)?;
+
+                self.update_active_window(window)?;
+
+                if window != self.root.id() && window > 1 {
+                    let window = self.backend.window(window)?;
+                    if !self.backend.window_type(&window)?.contains(&EwmhWindowType::Dock) {
+                        self.set_border(&window)?;
+
+                        self.set_wm_state(window.id(), WmState::DemandsAttention, |_| false)?;
+
+                        self.subscribers.emit(Notification::Focus(window.id()));
+
+                        if self.monitors.client_mut(window.id()).is_some() {
+                            self.remember_focus(window.id());
+                        }
+                    }
+                }
+            }
+            Event::ButtonEvent {
+                kind,
+                coordinates,
+                subwindow,
+                button,
+                ..
+            } => match kind {
+                EventKind::Press => {
+                    if !self.monitors.is_tiled(subwindow) && self.config.windows.mouse_movement {
+                        let window = self.backend.window(subwindow)?;
+
+                        window.raise()?;
+
+                        window.grab_pointer(
+                            vec![EventMask::PointerMotion, EventMask::ButtonRelease],
+                            Cursor::Nop,
+                            PointerMode::Asynchronous,
+                            KeyboardMode::Asynchronous,
+                            true,
+                            0,
+                        )?;
+
+                        let geometry = window.get_geometry()?;
+
+                        let hints = self
+                            .monitors
+                            .client_mut(subwindow)
+                            .map(|client| client.hints)
+                            .unwrap_or_default();
+
+                        self.grab.replace(Grab::new(
+                            button,
+                            window,
+                            geometry,
+                            hints,
+                            coordinates.root_x,
+                            coordinates.root_y,
+                        ));
+                    }
+                }
+                EventKind::Release => {
+                    if self.grab.is_some() {
+                        self.backend.display.ungrab_pointer()?;
+
+                        self.grab = None;
+                    }
+                }
+            },
+            Event::MotionNotify { coordinates, .. } => {
+                log::write(
+                    format!("motion notify: {:?}\n", coordinates),
+                    Severity::Info,
+                )?;
+
+                if let Some(grab) = &mut self.grab {
+                    let x_diff = coordinates.root_x as i16 - grab.x as i16;
+                    let y_diff = coordinates.root_y as i16 - grab.y as i16;
+
+                    match grab.button {
+                        Button::Button1 => {
+                            grab.window.mov(
+                                (grab.geometry.x as i16 + x_diff) as u16,
+                                (grab.geometry.y as i16 + y_diff) as u16,
+                            )?;
+                        }
+                        Button::Button3 => {
+                            let (width, height) = grab.hints.constrain(
+                                (grab.geometry.width as i16 + x_diff) as u16,
+                                (grab.geometry.height as i16 + y_diff) as u16,
+                                true,
+                            );
+
+                            grab.window.resize(width, height)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::ClientMessage {
+                window,
+                type_,
+                data: ClientMessageData::Long([action, first, second, ..]),
+                ..
+            } if type_.id() == self.atoms.net_wm_state.id() => {
+                log::write(
+                    format!("wm state message: {}, action: {}\n", window, action),
+                    Severity::Info,
+                )?;
+
+                for atom in [first, second] {
+                    if let Some(state) = self.atoms.net_wm_state(atom) {
+                        self.set_wm_state(window, state, |enabled| match action {
+                            NET_WM_STATE_REMOVE => false,
+                            NET_WM_STATE_ADD => true,
+                            _ => !enabled,
+                        })?;
+                    }
+                }
+            }
+            Event::ClientMessage { window, type_, .. }
+                if type_.id() == self.atoms.net_active_window.id() =>
+            {
+                log::write(format!("activate request: {}\n", window), Severity::Info)?;
+
+                self.activate(window)?;
+            }
+            Event::ClientMessage {
+                window,
+                type_,
+                data: ClientMessageData::Long([desktop, ..]),
+                ..
+            } if type_.id() == self.atoms.net_wm_desktop.id() => {
+                log::write(
+                    format!("desktop request: {}, desktop: {}\n", window, desktop),
+                    Severity::Info,
+                )?;
+
+                let len = self
+                    .monitors
+                    .monitors
+                    .first()
+                    .map(|monitor| monitor.workspace.len())
+                    .unwrap_or_default();
+
+                if desktop == ALL_DESKTOPS {
+                    self.set_wm_state(window, WmState::Sticky, |_| true)?;
+                } else if len > 0
+                    && self.monitors.move_client(
+                        window,
+                        desktop as usize / len,
+                        desktop as usize % len,
+                    )
+                {
+                    self.tile()?;
+                }
+            }
+            Event::ConfigureRequest { window, values } => {
+                log::write(
+                    format!("configure request: {}, values: {:?}\n", window, values),
+                    Severity::Info,
+                )?;
+
+                let window = self.backend.window(window)?;
+                if self.backend.window_type(&window)?.contains(&EwmhWindowType::Dock) {
+                    window.configure(ValuesBuilder::new(values))?;
+
+                    if self.monitors.dock_mut(window.id()).is_some() {
+                        self.place_dock(window)?;
+                    }
+                }
+            }
+            Event::PropertyNotify { window, atom, .. } if atom == self.atoms.wm_normal_hints.id() => {
+                log::write(format!("size hints changed: {}\n", window), Severity::Info)?;
+
+                let hints = self.backend.window(window)?.property(self.atoms.wm_normal_hints, self.atoms.wm_size_hints)?;
+
+                if let Some(client) = self.monitors.client_mut(window) {
+                    client.hints = SizeHints::new(&hints);
+
+                    self.tile()?;
+                }
+            }
+            Event::PropertyNotify { window, atom, .. }
+                if atom == self.atoms.net_wm_strut.id() || atom == self.atoms.net_wm_strut_partial.id() =>
+            {
+                log::write(format!("strut changed: {}\n", window), Severity::Info)?;
+
+                let window = self.backend.window(window)?;
+                let strut = self.read_strut(&window)?;
+
+                if let Some(dock) = self.monitors.dock_mut(window.id()) {
+                    dock.strut = strut;
+
+                    self.reserve()?;
+                }
+            }
+            Event::DestroyNotify { window, .. } => {
+                log::write(format!("destroy notify: {}\n", window), Severity::Info)?;
+
+                if self.monitors.remove_dock(window).is_some() {
+                    self.reserve()?;
+                }
+
+                if self.unmanage(window)?.is_some() {
+                    self.subscribers.emit(Notification::Unmap(window));
+
+                    self.tile()?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.setup()?;
+
+        log::write("yaxiwm is running\n", Severity::Info)?;
+
+        listen(self.backend.display.clone(), self.events.clone());
+
+        // TODO: the bspwm config runs this to start the bar
+        //
+        // ~/.config/polybar/launch.sh &
+
+        while !self.should_close {
+            match self.events.wait()? {
+                EventType::XEvent(event) => {
+                    self.handle_event(event)?;
+                },
+                EventType::Config(sequence, mut stream) => {
+                    let reply = match sequence.request {
+                        Request::Subscribe if sequence.value() == 0 => {
+                            Reply::error(Status::InvalidArgument, "no topics to subscribe to")
+                        }
+                        Request::Subscribe => match stream
+                            .try_clone()
+                            .and_then(|clone| self.subscribers.subscribe(sequence.value(), clone))
+                        {
+                            Ok(()) => Reply::ok(),
+                            Err(err) => {
+                                log::write(format!("subscribe failed: {}\n", err), Severity::Error)?;
+
+                                Reply::error(Status::Failed, err.to_string())
+                            }
+                        },
+                        _ => match self.handle_sequence(sequence) {
+                            Ok(reply) => reply,
+                            Err(err) => {
+                                log::write(format!("request failed: {}\n", err), Severity::Error)?;
+
+                                Reply::error(Status::Failed, err.to_string())
+                            }
+                        },
+                    };
+
+                    if let Err(err) = stream.reply(reply) {
+                        log::write(format!("failed to reply: {}\n", err), Severity::Error)?;
+                    }
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn no_focus() -> Reply {
+    Reply::error(Status::NoFocus, "no focused client")
+}
+
+fn listen(display: Display, events: EventQueue) {
+    thread::spawn(move || {
+        loop {
+            let event = display.next_event().expect("failed to listen");
+
+            events.push(EventType::XEvent(event)).expect("failed to push");
+        }
+    });
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::fake::{Call, Fake, ROOT};
+
+    use proto::Value;
+
+    fn manager(screens: &[Area]) -> (Fake, WindowManager<Fake>) {
+        let fake = Fake::new(screens);
+        let mut wm = WindowManager::with_backend(fake.clone()).unwrap();
+
+        wm.load_monitors().unwrap();
+
+        let reply = wm.handle_sequence(Sequence::new(Request::WorkspacePerMonitor, 3)).unwrap();
+
+        assert_eq!(reply, Reply::ok());
+
+        (fake, wm)
+    }
+
+    fn manage(fake: &Fake, wm: &mut WindowManager<Fake>, wid: u32) {
+        let window = fake.create(wid, Vec::new());
+
+        wm.monitors
+            .focused(|_, monitor| {
+                monitor.workspace.insert(Client::new(window.clone(), State::Tiled));
+
+                Ok(())
+            })
+            .unwrap();
+
+        fake.set_focus(wid);
+
+        wm.tile().unwrap();
+    }
+
+    #[test]
+    fn tiles_clients_with_the_workspace_layout() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        manage(&fake, &mut wm, 10);
+        manage(&fake, &mut wm, 11);
+
+        assert_eq!(fake.geometry(10), Area::new(0, 0, 500, 800));
+        assert_eq!(fake.geometry(11), Area::new(500, 0, 500, 800));
+        assert_eq!(fake.property(ROOT, "_NET_CLIENT_LIST"), vec![10, 11]);
+    }
+
+    #[test]
+    fn switching_workspace_hides_clients_without_losing_them() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        manage(&fake, &mut wm, 10);
+
+        let reply = wm.handle_sequence(Sequence::new(Request::Workspace, 2)).unwrap();
+
+        assert_eq!(reply, Reply::ok());
+        assert!(!fake.is_mapped(10));
+        assert_eq!(fake.property(ROOT, "_NET_CURRENT_DESKTOP"), vec![1]);
+        assert_eq!(fake.property(10, "WM_STATE"), vec![WM_STATE_ICONIC, 0]);
+
+        let client = wm.monitors.client_mut(10).unwrap();
+
+        assert!(client.expects_unmap());
+        assert!(!client.expects_unmap());
+
+        wm.handle_sequence(Sequence::new(Request::Workspace, 1)).unwrap();
+
+        assert!(fake.is_mapped(10));
+        assert_eq!(fake.property(10, "WM_STATE"), vec![WM_STATE_NORMAL, 0]);
+    }
+
+    #[test]
+    fn rejects_workspaces_out_of_range() {
+        let (_, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        let reply = wm.handle_sequence(Sequence::new(Request::Workspace, 4)).unwrap();
+
+        assert_eq!(
+            reply,
+            Reply::error(Status::InvalidArgument, "workspace 4 is out of range 1-3")
+        );
+    }
+
+    #[test]
+    fn send_to_workspace_needs_a_focused_client() {
+        let (_, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        let reply = wm.handle_sequence(Sequence::new(Request::SendToWorkspace, 2)).unwrap();
+
+        assert_eq!(reply, no_focus());
+    }
+
+    #[test]
+    fn send_to_workspace_moves_the_focused_client() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        manage(&fake, &mut wm, 10);
+
+        wm.handle_sequence(Sequence::new(Request::SendToWorkspace, 3)).unwrap();
+
+        assert_eq!(wm.monitors.locate(10), Some((0, 2)));
+        assert_eq!(fake.property(10, "_NET_WM_DESKTOP"), vec![2]);
+        assert!(!fake.is_mapped(10));
+    }
+
+    #[test]
+    fn monitor_circulate_moves_the_focused_client() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800), Area::new(1000, 0, 1000, 800)]);
+
+        manage(&fake, &mut wm, 10);
+
+        let reply = wm.handle_sequence(Sequence::new(Request::MonitorCirculate, 0)).unwrap();
+
+        assert_eq!(reply, Reply::ok());
+        assert_eq!(wm.monitors.locate(10), Some((1, 0)));
+        assert_eq!(fake.geometry(10), Area::new(1000, 0, 1000, 800));
+    }
+
+    #[test]
+    fn workspace_switches_the_monitor_under_the_pointer() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800), Area::new(1000, 0, 1000, 800)]);
+
+        fake.set_pointer(1500, 400);
+
+        wm.handle_sequence(Sequence::new(Request::Workspace, 2)).unwrap();
+
+        let reply = wm.handle_sequence(Sequence::new(Request::QueryWorkspaces, 0)).unwrap();
+
+        assert_eq!(reply, Reply::Workspaces(vec![1, 2]));
+        assert_eq!(fake.property(ROOT, "_NET_CURRENT_DESKTOP"), vec![4]);
+    }
+
+    #[test]
+    fn fullscreen_covers_the_monitor_without_borders() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        wm.config.padding.top = 30;
+        wm.config.windows.borders.width = 2;
+
+        manage(&fake, &mut wm, 10);
+
+        wm.handle_sequence(Sequence::new(Request::FullscreenToggle, 0)).unwrap();
+
+        assert_eq!(fake.geometry(10), Area::new(0, 0, 1000, 800));
+        assert_eq!(fake.border(10).0, 0);
+
+        wm.handle_sequence(Sequence::new(Request::FullscreenToggle, 0)).unwrap();
+
+        assert_eq!(fake.geometry(10), Area::new(0, 30, 1000, 770));
+        assert_eq!(fake.border(10).0, 2);
+    }
+
+    #[test]
+    fn close_reaches_the_focused_client() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        manage(&fake, &mut wm, 10);
+
+        fake.calls();
+
+        wm.handle_sequence(Sequence::new(Request::Close, 0)).unwrap();
+
+        assert_eq!(fake.calls(), vec![Call::Close(10)]);
+    }
+
+    #[test]
+    fn float_toggle_takes_the_client_out_of_the_layout() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        manage(&fake, &mut wm, 10);
+        manage(&fake, &mut wm, 11);
+
+        wm.handle_sequence(Sequence::new(Request::FloatToggle, 0)).unwrap();
+
+        assert_eq!(fake.geometry(10), Area::new(0, 0, 1000, 800));
+
+        let reply = wm.handle_sequence(Sequence::new(Request::FloatRight, 10)).unwrap();
+
+        assert_eq!(reply, Reply::ok());
+        assert_eq!(fake.geometry(11), Area::new(510, 0, 500, 800));
+    }
+
+    #[test]
+    fn resize_keeps_clients_of_removed_workspaces() {
+        let fake = Fake::new(&[]);
+        let mut workspaces = Workspaces::new();
+
+        workspaces.resize(3);
+        workspaces.current = 2;
+        workspaces.insert_at(2, Client::new(fake.create(10, Vec::new()), State::Tiled));
+
+        workspaces.resize(2);
+
+        assert_eq!(workspaces.len(), 2);
+        assert_eq!(workspaces.current, 1);
+        assert_eq!(workspaces.position(10), Some(1));
+    }
+
+    #[test]
+    fn struts_only_reserve_the_monitors_they_reach() {
+        let screen = Area::new(0, 0, 2000, 800);
+        let strut = Strut::partial(&[0, 0, 30, 0, 0, 0, 0, 0, 0, 999, 0, 0]);
+
+        assert_eq!(strut.reserve(Area::new(0, 0, 1000, 800), screen).top, 30);
+        assert_eq!(strut.reserve(Area::new(1000, 0, 1000, 800), screen).top, 0);
+    }
+
+    #[test]
+    fn rules_place_matching_clients() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        wm.config.windows.borders.width = 2;
+        wm.config.rules.push(Rule::parse("class=mpv state=float workspace=2 geometry=10,20,300,200 border-width=0").unwrap());
+
+        let window = fake.create(10, Vec::new());
+
+        fake.set_text(10, "WM_CLASS", &["gl", "mpv"]);
+
+        wm.manage(window, &[]).unwrap();
+
+        assert!(!fake.is_mapped(10));
+        assert_eq!(fake.property(10, "_NET_WM_DESKTOP"), vec![1]);
+        assert_eq!(fake.property(10, "WM_STATE"), vec![WM_STATE_ICONIC, 0]);
+
+        wm.handle_sequence(Sequence::new(Request::Workspace, 2)).unwrap();
+
+        assert!(fake.is_mapped(10));
+        assert_eq!(fake.geometry(10), Area::new(10, 20, 300, 200));
+        assert_eq!(fake.border(10).0, 0);
+    }
+
+    #[test]
+    fn unmatched_clients_are_tiled_and_focused() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        wm.config.rules.push(Rule::parse("class=mpv focus=false").unwrap());
+
+        let window = fake.create(10, Vec::new());
+
+        fake.set_text(10, "WM_CLASS", &["st", "St"]);
+
+        wm.manage(window, &[]).unwrap();
+
+        assert!(fake.is_mapped(10));
+        assert_eq!(fake.geometry(10), Area::new(0, 0, 1000, 800));
+        assert_eq!(wm.backend.input_focus().unwrap(), 10);
+
+        let window = fake.create(11, Vec::new());
+
+        fake.set_text(11, "WM_CLASS", &["mpv", "mpv"]);
+
+        wm.manage(window, &[]).unwrap();
+
+        assert!(fake.is_mapped(11));
+        assert_eq!(wm.backend.input_focus().unwrap(), 10);
+    }
+
+    #[test]
+    fn rules_are_managed_over_ipc() {
+        let (_, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        for rule in ["class=mpv state=float", "role=\"pop up\" focus=false"] {
+            let sequence = Sequence::with_values(Request::RuleAdd, vec![Value::String(rule.to_string())]);
+
+            assert_eq!(wm.handle_sequence(sequence).unwrap(), Reply::ok());
+        }
+
+        let invalid = Sequence::with_values(Request::RuleAdd, vec![Value::String(String::from("state=float"))]);
+
+        assert!(matches!(wm.handle_sequence(invalid).unwrap(), Reply::Status(Status::InvalidArgument, _)));
+
+        wm.handle_sequence(Sequence::new(Request::RuleRemove, 1)).unwrap();
+
+        assert_eq!(
+            wm.handle_sequence(Sequence::new(Request::RuleList, 0)).unwrap(),
+            Reply::Rules(vec![String::from("role=\"pop up\" focus=false")])
+        );
+
+        let reply = wm.handle_sequence(Sequence::new(Request::RuleRemove, 2)).unwrap();
+
+        assert!(matches!(reply, Reply::Status(Status::InvalidArgument, _)));
+    }
+
+    #[test]
+    fn size_hints_clamp_and_snap_to_increments() {
+        // a terminal with 8x16 cells and a 4x4 base size
+        let terminal = SizeHints::new(&[
+            P_MIN_SIZE | P_RESIZE_INC | P_BASE_SIZE, 0, 0, 0, 0, 20, 36, 0, 0, 8, 16, 0, 0, 0, 0, 4, 4, 0,
+        ]);
+
+        assert_eq!(terminal.constrain(503, 800, false), (500, 788));
+        assert_eq!(terminal.constrain(10, 10, false), (20, 36));
+
+        let video = SizeHints::new(&[P_ASPECT | P_MAX_SIZE, 0, 0, 0, 0, 0, 0, 1920, 1080, 0, 0, 16, 9, 16, 9]);
+
+        assert_eq!(video.constrain(800, 800, true), (800, 450));
+        assert_eq!(video.constrain(400, 100, true), (177, 100));
+        assert_eq!(video.constrain(4000, 4000, false), (1920, 1080));
+        assert!(!video.is_fixed());
+    }
+
+    #[test]
+    fn huge_size_hints_do_not_overflow() {
+        let aspect = SizeHints::new(&[P_ASPECT, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, u32::MAX, 1, 1, 0x0100_0000]);
+
+        assert_eq!(aspect.constrain(800, 600, true), (1, 600));
+
+        let base = SizeHints::new(&[
+            P_BASE_SIZE | P_ASPECT, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, u32::MAX, 1, 1, u32::MAX, u32::MAX, u32::MAX,
+        ]);
+
+        assert_eq!(base.constrain(800, 600, true), (u16::MAX, u16::MAX));
+    }
+
+    #[test]
+    fn fixed_size_clients_float_and_size_hints_are_optional_when_tiled() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        let terminal = fake.create(10, Vec::new());
+        let dialog = fake.create(11, Vec::new());
+
+        terminal.set_property(
+            wm.atoms.wm_normal_hints,
+            wm.atoms.wm_size_hints,
+            &[P_RESIZE_INC, 0, 0, 0, 0, 0, 0, 0, 0, 7, 15, 0, 0, 0, 0],
+        ).unwrap();
+
+        dialog.set_property(
+            wm.atoms.wm_normal_hints,
+            wm.atoms.wm_size_hints,
+            &[P_MIN_SIZE | P_MAX_SIZE, 0, 0, 0, 0, 300, 200, 300, 200, 0, 0, 0, 0, 0, 0],
+        ).unwrap();
+
+        wm.manage(terminal, &[]).unwrap();
+        wm.manage(dialog, &[]).unwrap();
+
+        assert_eq!(fake.geometry(10), Area::new(0, 0, 1000, 800));
+        assert!(!wm.monitors.is_tiled(11));
+
+        wm.config.windows.size_hints = true;
+        wm.tile().unwrap();
+
+        assert_eq!(fake.geometry(10), Area::new(0, 0, 994, 795));
+    }
+
+    #[test]
+    fn transients_follow_their_parent() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800), Area::new(1000, 0, 1000, 800)]);
+
+        manage(&fake, &mut wm, 10);
+
+        wm.handle_sequence(Sequence::new(Request::MonitorCirculate, 0)).unwrap();
+
+        // the pointer stays on the first monitor, the parent now lives on the second
+        let dialog = fake.create(11, Vec::new());
+
+        dialog.set_property(wm.atoms.wm_transient_for, wm.atoms.window, &[10]).unwrap();
+
+        fake.calls();
+
+        wm.manage(dialog, &[]).unwrap();
+
+        assert_eq!(wm.monitors.locate(11), Some((1, 0)));
+        assert_eq!(fake.geometry(11), Area::new(1450, 350, 100, 100));
+        assert_eq!(wm.monitors.client_mut(11).unwrap().state, State::Float);
+
+        assert!(fake.calls().contains(&Call::Raise(11)));
+
+        fake.set_focus(10);
+
+        wm.handle_sequence(Sequence::new(Request::SendToWorkspace, 2)).unwrap();
+
+        assert_eq!(wm.monitors.locate(10), Some((0, 1)));
+        assert_eq!(wm.monitors.locate(11), Some((0, 1)));
+    }
+
+    #[test]
+    fn scratchpads_toggle_on_the_focused_monitor() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        manage(&fake, &mut wm, 10);
+        manage(&fake, &mut wm, 11);
+
+        let add = Sequence::with_values(Request::ScratchpadAdd, vec![Value::String(String::from("term"))]);
+        let toggle = |name: &str| Sequence::with_values(Request::ScratchpadToggle, vec![Value::String(name.to_string())]);
+
+        assert_eq!(wm.handle_sequence(add.clone()).unwrap(), Reply::ok());
+
+        assert!(!fake.is_mapped(11));
+        assert_eq!(fake.geometry(10), Area::new(0, 0, 1000, 800));
+        assert_eq!(wm.monitors.clients(), vec![10]);
+
+        fake.set_focus(10);
+
+        assert!(matches!(wm.handle_sequence(add).unwrap(), Reply::Status(Status::InvalidArgument, _)));
+
+        assert_eq!(wm.handle_sequence(toggle("term")).unwrap(), Reply::ok());
+
+        assert!(fake.is_mapped(11));
+        assert_eq!(fake.geometry(11), Area::new(250, 0, 500, 800));
+        assert_eq!(fake.geometry(10), Area::new(0, 0, 1000, 800));
+        assert_eq!(wm.backend.input_focus().unwrap(), 11);
+
+        wm.handle_sequence(toggle("term")).unwrap();
+
+        assert!(!fake.is_mapped(11));
+
+        let reply = wm.handle_sequence(toggle("notes")).unwrap();
+
+        assert!(matches!(reply, Reply::Status(Status::InvalidArgument, _)));
+    }
+
+    #[test]
+    fn scratchpad_windows_are_claimed_when_they_map() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        wm.config.rules.push(Rule::parse("class=notes scratchpad=notes").unwrap());
+
+        // a toggle spawned the terminal, the notes app and an unrelated window started on their own
+        wm.pending_scratchpads.push((String::from("term"), 4242));
+
+        for (wid, class, pid) in [(12, "browser", 4000), (10, "dropdown", 4242), (11, "notes", 4001)] {
+            let window = fake.create(wid, Vec::new());
+
+            fake.set_text(wid, "WM_CLASS", &[class, class]);
+
+            window.set_property(wm.atoms.net_wm_pid, wm.atoms.cardinal, &[pid]).unwrap();
+
+            wm.manage(window, &[]).unwrap();
+        }
+
+        assert!(fake.is_mapped(10));
+        assert_eq!(fake.geometry(10), Area::new(450, 350, 100, 100));
+        assert!(!fake.is_mapped(11));
+        assert!(wm.pending_scratchpads.is_empty());
+        assert_eq!(wm.monitors.scratchpad("term"), Some(10));
+        assert_eq!(wm.monitors.scratchpad("notes"), Some(11));
+        assert_eq!(fake.geometry(12), Area::new(0, 0, 1000, 800));
+    }
+
+    #[test]
+    fn swapping_reorders_the_tiled_clients() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        for wid in [10, 11, 12] {
+            manage(&fake, &mut wm, wid);
+        }
+
+        let mut order = |request| {
+            assert_eq!(wm.handle_sequence(Sequence::new(request, 0)).unwrap(), Reply::ok());
+
+            wm.monitors.clients()
+        };
+
+        assert_eq!(order(Request::SwapUp), vec![10, 12, 11]);
+        assert_eq!(order(Request::SwapMaster), vec![12, 10, 11]);
+        assert_eq!(order(Request::SwapUp), vec![12, 10, 11]);
+        assert_eq!(order(Request::SwapMaster), vec![10, 12, 11]);
+        assert_eq!(order(Request::SwapDown), vec![10, 11, 12]);
+        assert_eq!(order(Request::RotateStack), vec![12, 10, 11]);
+
+        assert_eq!(fake.geometry(12), Area::new(0, 0, 500, 800));
+    }
+
+    #[test]
+    fn swapping_skips_floating_clients() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
+
+        for wid in [10, 11, 12] {
+            manage(&fake, &mut wm, wid);
+        }
+
+        fake.set_focus(11);
+
+        wm.handle_sequence(Sequence::new(Request::FloatToggle, 0)).unwrap();
+
+        fake.set_focus(12);
+
+        wm.handle_sequence(Sequence::new(Request::SwapUp, 0)).unwrap();
+
+        assert_eq!(wm.monitors.clients(), vec![12, 11, 10]);
+    }
+
+    #[test]
+    fn focus_follows_the_direction_on_screen() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800), Area::new(1000, 0, 1000, 800)]);
+
+        for wid in [10, 11, 12] {
+            manage(&fake, &mut wm, wid);
+        }
+
+        let focus = |wm: &mut WindowManager<Fake>, direction: &str| {
+            let sequence = Sequence::with_values(Request::FocusDir, vec![Value::String(direction.to_string())]);
+
+            wm.handle_sequence(sequence).unwrap();
 
-        ewmh.set_supporting_wm_check(window.id())?;
+            fake.calls();
 
-        ewmh.set_wm_name("yaxi")?;
+            wm.backend.input_focus().unwrap()
+        };
 
-        let root = self.display.use_ewmh(&self.root);
+        assert_eq!(focus(&mut wm, "left"), 10);
+        assert_eq!(focus(&mut wm, "right"), 11);
+        assert_eq!(focus(&mut wm, "down"), 12);
+        assert_eq!(focus(&mut wm, "down"), 12);
 
-        root.set_supporting_wm_check(window.id())?;
+        // the second monitor is empty, so only the pointer moves over
+        let sequence = Sequence::with_values(Request::FocusDir, vec![Value::String(String::from("right"))]);
 
-        // TODO: support for _NET_WM_STATE and _NET_WM_STATE_FULLSCREEN
-
-        root.set_supported(&[
-            self.display.intern_atom("WM_PROTOCOLS", false)?,
-            self.display.intern_atom("WM_DELETE_WINDOW", false)?,
-            self.display.intern_atom("_NET_ACTIVE_WINDOW", false)?,
-            self.display.intern_atom("_NET_NUMBER_OF_DESKTOPS", false)?,
-            self.display.intern_atom("_NET_CURRENT_DESKTOP", false)?,
-            self.display.intern_atom("_NET_WM_WINDOW_TYPE", false)?,
-            self.display.intern_atom("_NET_WM_WINDOW_TYPE_DESKTOP", false)?,
-            self.display.intern_atom("_NET_WM_WINDOW_TYPE_DOCK", false)?,
-            self.display.intern_atom("_NET_WM_WINDOW_TYPE_TOOLBAR", false)?,
-            self.display.intern_atom("_NET_WM_WINDOW_TYPE_MENU", false)?,
-            self.display.intern_atom("_NET_WM_WINDOW_TYPE_UTILITY", false)?,
-            self.display.intern_atom("_NET_WM_WINDOW_TYPE_SPLASH", false)?,
-            self.display.intern_atom("_NET_WM_WINDOW_TYPE_DIALOG", false)?,
-            self.display.intern_atom("_NET_WM_WINDOW_TYPE_NORMAL", false)?,
-        ])?;
+        wm.handle_sequence(sequence).unwrap();
 
-        Ok(())
-    }
+        assert_eq!(fake.calls(), vec![Call::WarpPointer(1500, 400)]);
 
-    fn tile(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        self.monitors.all(|_, monitor| {
-            monitor.workspace.tile(
-                monitor.area.pad(self.config.padding),
-                self.config.windows.gaps,
-            )
-        })?;
+        let sequence = Sequence::with_values(Request::FocusDir, vec![Value::String(String::from("north"))]);
 
-        Ok(())
+        assert_eq!(
+            wm.handle_sequence(sequence).unwrap(),
+            Reply::error(Status::InvalidArgument, "unknown direction: north"),
+        );
     }
 
-    fn focused_client<F>(&mut self, f: F) -> Result<(), Box<dyn std::error::Error>>
-    where
-        F: Fn(&mut Client) -> Result<(), Box<dyn std::error::Error>>,
-    {
-        let focus = self.display.get_input_focus()?;
+    #[test]
+    fn swapping_by_direction_crosses_monitors() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800), Area::new(1000, 0, 1000, 800)]);
 
-        self.monitors.focused(|_, monitor| {
-            if let Some(index) = monitor.workspace.find(focus.window) {
-                f(&mut monitor.workspace.workspaces[monitor.workspace.current][index])?;
-            }
+        for wid in [10, 11, 12] {
+            manage(&fake, &mut wm, wid);
+        }
 
-            Ok(())
-        })
-    }
+        let swap = |wm: &mut WindowManager<Fake>, direction: &str| {
+            let sequence = Sequence::with_values(Request::SwapDir, vec![Value::String(direction.to_string())]);
 
-    fn monitor_circulate(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let focus = self.display.get_input_focus()?;
+            wm.handle_sequence(sequence).unwrap()
+        };
 
-        if let Some((count, client)) = self.monitors.extract_client(focus.window)? {
-            if self.monitors.monitors.len() > 0 {
-                let index = (count + 1 >= self.monitors.monitors.len())
-                    .then(|| 0)
-                    .unwrap_or(count + 1);
+        assert_eq!(swap(&mut wm, "left"), Reply::ok());
+        assert_eq!(wm.monitors.clients(), vec![12, 11, 10]);
+        assert_eq!(fake.geometry(12), Area::new(0, 0, 500, 800));
 
-                self.monitors.monitors[index].workspace.insert(client);
-            }
-        }
+        assert_eq!(swap(&mut wm, "right"), Reply::ok());
+        assert_eq!(wm.monitors.clients(), vec![10, 11, 12]);
 
-        self.tile()
-    }
+        assert_eq!(swap(&mut wm, "right"), Reply::ok());
 
-    fn mov_resize_focused<F>(&mut self, transform: F) -> Result<(), Box<dyn std::error::Error>>
-    where
-        F: Fn(u16, u16, u16, u16) -> (u16, u16, u16, u16),
-    {
-        self.focused_client(|client| {
-            if client.state == State::Float {
-                let geometry = client.window.get_geometry()?;
+        assert_eq!(wm.monitors.locate(12), Some((1, 0)));
+        assert_eq!(fake.geometry(12), Area::new(1000, 0, 1000, 800));
+        assert_eq!(fake.geometry(11), Area::new(500, 0, 500, 800));
+        assert_eq!(wm.backend.input_focus().unwrap(), 12);
+    }
 
-                let (x, y, width, height) =
-                    transform(geometry.x, geometry.y, geometry.width, geometry.height);
+    #[test]
+    fn focus_wraps_around_within_its_scope() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
 
-                client.window.mov_resize(x, y, width, height)?;
-            }
+        for wid in [10, 11, 12, 13] {
+            manage(&fake, &mut wm, wid);
+        }
 
-            Ok(())
-        })
-    }
+        fake.set_focus(11);
 
-    fn update_borders(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let focus = self.display.get_input_focus()?;
+        wm.handle_sequence(Sequence::new(Request::FloatToggle, 0)).unwrap();
 
-        if focus.window != self.root.id() && focus.window > 1 {
-            let window = self.display.window_from_id(focus.window)?;
+        let mut focus = |request: Request| {
+            wm.handle_sequence(Sequence::new(request, 0)).unwrap();
 
-            self.set_border(&window)?;
-        }
+            wm.backend.input_focus().unwrap()
+        };
 
-        Ok(())
-    }
+        assert_eq!(focus(Request::FocusDown), 12);
+        assert_eq!(focus(Request::FocusDown), 13);
+        assert_eq!(focus(Request::FocusDown), 10);
+        assert_eq!(focus(Request::FocusUp), 13);
 
-    fn set_border(&mut self, window: &Window) -> Result<(), Box<dyn std::error::Error>> {
-        let ewmh = self.display.use_ewmh(window);
+        assert_eq!(focus(Request::FocusFloatToggle), 11);
+        assert_eq!(focus(Request::FocusFloatToggle), 10);
 
-        if !ewmh.get_wm_window_type()?.contains(&EwmhWindowType::Dock) {
-            let borders = self.config.windows.borders;
+        let scope = Sequence::with_values(Request::FocusScope, vec![Value::String(String::from("tiled"))]);
 
-            self.monitors.all(|_, monitor| {
-                monitor.workspace.map_clients(|client| {
-                    client.window.set_border_width(borders.width)?;
+        assert_eq!(wm.handle_sequence(scope).unwrap(), Reply::ok());
 
-                    client
-                        .window
-                        .set_border_pixel(borders.normal)
-                        .map_err(|err| err.into())
-                })
-            })?;
+        let mut focus = |request: Request| {
+            wm.handle_sequence(Sequence::new(request, 0)).unwrap();
 
-            window.set_border_pixel(borders.focused)?;
-        }
+            wm.backend.input_focus().unwrap()
+        };
 
-        Ok(())
+        assert_eq!(focus(Request::FocusDown), 12);
+        assert_eq!(focus(Request::FocusUp), 10);
+        assert_eq!(focus(Request::FocusUp), 13);
     }
 
-    fn handle_sequence(&mut self, sequence: Sequence) -> Result<(), Box<dyn std::error::Error>> {
-            match sequence.request {
-                Request::Workspace => {
-                    self.monitors.focused(|count, monitor| {
-                        if sequence.value.max(1) - 1 < monitor.workspace.len() as u32 {
-                            monitor.workspace.current = sequence.value.max(1) as usize - 1;
+    #[test]
+    fn focus_history_is_most_recent_first() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
 
-                            self.display
-                                .use_ewmh(&self.root)
-                                .set_current_desktop((monitor.workspace.len() * count) as u32 + monitor.workspace.current as u32)?;
-                        }
+        for wid in [10, 11, 12, 13] {
+            manage(&fake, &mut wm, wid);
 
-                        monitor.workspace.tile(
-                            monitor.area.pad(self.config.padding),
-                            self.config.windows.gaps,
-                        )
-                    })?;
-                }
-                Request::Kill => {
-                    self.focused_client(|client| client.window.kill().map_err(|err| err.into()))?;
-                }
-                Request::Close => {
-                    let atoms = self.atoms.clone();
+            wm.remember_focus(wid);
+        }
 
-                    self.focused_client(|client| {
-                        client
-                            .window
-                            .send_event(
-                                Event::ClientMessage {
-                                    format: 32,
-                                    window: client.window.id(),
-                                    type_: atoms.wm_protocols,
-                                    data: ClientMessageData::Long([
-                                        atoms.wm_delete.id(),
-                                        0,
-                                        0,
-                                        0,
-                                        0,
-                                    ]),
-                                },
-                                vec![],
-                                false,
-                            )
-                            .map_err(|err| err.into())
-                    })?;
-                }
-                Request::FocusUp | Request::FocusDown | Request::FocusMaster => {
-                    let focus = self.display.get_input_focus()?;
+        // the backend reports no focus events, so each request is followed by the one X would send
+        let mut focus = |request: Request| {
+            wm.handle_sequence(Sequence::new(request, 0)).unwrap();
 
-                    self.monitors.focused(|_, monitor| match sequence.request {
-                        Request::FocusUp => monitor
-                            .workspace
-                            .change_focus(focus.window, |index| index.max(1) - 1),
-                        Request::FocusDown => monitor
-                            .workspace
-                            .change_focus(focus.window, |index| index + 1),
-                        Request::FocusMaster => monitor.workspace.change_focus(focus.window, |_| 0),
-                        _ => Ok(()),
-                    })?;
-                }
-                Request::PaddingTop
-                | Request::PaddingBottom
-                | Request::PaddingLeft
-                | Request::PaddingRight
-                | Request::WindowGaps => {
-                    match sequence.request {
-                        Request::PaddingTop => self.config.padding.top = sequence.value as u16,
-                        Request::PaddingBottom => {
-                            self.config.padding.bottom = sequence.value as u16
-                        }
-                        Request::PaddingLeft => self.config.padding.left = sequence.value as u16,
-                        Request::PaddingRight => self.config.padding.right = sequence.value as u16,
-                        Request::WindowGaps => self.config.windows.gaps = sequence.value as u16,
-                        _ => unreachable!(),
-                    }
+            let focus = wm.backend.input_focus().unwrap();
 
-                    self.tile()?;
-                }
-                Request::FocusedBorder | Request::NormalBorder | Request::BorderWidth => {
-                    match sequence.request {
-                        Request::FocusedBorder => {
-                            self.config.windows.borders.focused = sequence.value
-                        }
-                        Request::NormalBorder => {
-                            self.config.windows.borders.normal = sequence.value
-                        }
-                        Request::BorderWidth => {
-                            self.config.windows.borders.width = sequence.value as u16
-                        }
-                        _ => unreachable!(),
-                    }
+            wm.remember_focus(focus);
 
-                    self.update_borders()?;
-                }
-                Request::FloatToggle => {
-                    self.focused_client(|client| {
-                        if client.state == State::Float {
-                            client.state = State::Tiled;
-                        } else if client.state != State::Dock {
-                            client.state = State::Float;
-                        }
+            focus
+        };
 
-                        Ok(())
-                    })?;
+        assert_eq!(focus(Request::FocusLast), 12);
+        assert_eq!(focus(Request::FocusLast), 13);
 
-                    self.tile()?;
-                }
-                Request::FloatRight => self.mov_resize_focused(|x, y, width, height| {
-                    (x + sequence.value as u16, y, width, height)
-                })?,
-                Request::FloatLeft => self.mov_resize_focused(|x, y, width, height| {
-                    (x - (sequence.value as u16).min(x), y, width, height)
-                })?,
-                Request::FloatUp => self.mov_resize_focused(|x, y, width, height| {
-                    (x, y - (sequence.value as u16).min(y), width, height)
-                })?,
-                Request::FloatDown => self.mov_resize_focused(|x, y, width, height| {
-                    (x, y + sequence.value as u16, width, height)
-                })?,
-                Request::ResizeRight => self.mov_resize_focused(|x, y, width, height| {
-                    (x, y, width + sequence.value as u16, height)
-                })?,
-                Request::ResizeLeft => self.mov_resize_focused(|x, y, width, height| {
-                    (x, y, width - (sequence.value as u16).min(width), height)
-                })?,
-                Request::ResizeUp => self.mov_resize_focused(|x, y, width, height| {
-                    (x, y, width, height - (sequence.value as u16).min(height))
-                })?,
-                Request::ResizeDown => self.mov_resize_focused(|x, y, width, height| {
-                    (x, y, width, height + sequence.value as u16)
-                })?,
-                Request::EnableMouse => self.config.windows.mouse_movement = true,
-                Request::DisableMouse => self.config.windows.mouse_movement = false,
-                Request::WorkspacePerMonitor => {
-                    self.monitors.all(|_, monitor| {
-                        monitor.workspace.resize(sequence.value as usize);
+        assert_eq!(focus(Request::FocusCycleMru), 12);
+        assert_eq!(focus(Request::FocusCycleMru), 11);
+        assert_eq!(focus(Request::FocusCycleMru), 10);
+        assert_eq!(focus(Request::FocusCycleMru), 13);
+        assert_eq!(focus(Request::FocusCycleMru), 12);
 
-                        Ok(())
-                    })?;
+        assert_eq!(focus(Request::FocusDown), 13);
+        assert_eq!(wm.history, vec![13, 12, 11, 10]);
 
-                    self.display
-                        .use_ewmh(&self.root)
-                        .set_number_of_desktops(sequence.value * self.monitors.monitors.len() as u32)?;
+        wm.unmanage(13).unwrap();
 
-                    self.update_viewport()?;
-                }
-                Request::MonitorCirculate => self.monitor_circulate()?,
-                Request::Quit => self.should_close = true,
-                Request::Unknown => {}
-            }
+        assert_eq!(wm.backend.input_focus().unwrap(), 12);
+        assert_eq!(wm.history, vec![12, 11, 10]);
 
-        Ok(())
+        // closing a client that is not focused leaves the focus alone
+        wm.unmanage(10).unwrap();
+
+        assert_eq!(wm.backend.input_focus().unwrap(), 12);
     }
 
-    fn handle_event(&mut self, event: Event) -> Result<(), Box<dyn std::error::Error>> {
-        match event {
-            Event::MapRequest { window, .. } => {
-                log::write(format!("map request: {}\n", window), Severity::Info)?;
+    #[test]
+    fn directions_cross_stacked_monitors() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800), Area::new(0, 800, 1000, 800)]);
 
-                let window = self.display.window_from_id(window)?;
-                let type_ = self.display.use_ewmh(&window).get_wm_window_type()?;
+        let direction = |request: Request, direction: &str| {
+            Sequence::with_values(request, vec![Value::String(direction.to_string())])
+        };
 
-                window.select_input(&[
-                    EventMask::SubstructureNotify,
-                    EventMask::SubstructureRedirect,
-                    EventMask::EnterWindow,
-                    EventMask::FocusChange,
-                ])?;
+        fake.set_pointer(500, 400);
 
-                window.map(WindowKind::Window)?;
+        let layout = Sequence::with_values(Request::Layout, vec![Value::String(String::from("monocle"))]);
 
-                if !type_.contains(&EwmhWindowType::Dock) {
-                    window.set_input_focus(RevertTo::Parent)?;
+        wm.handle_sequence(layout).unwrap();
 
-                    self.set_border(&window)?;
+        assert_eq!(wm.monitors.monitors[0].workspace.layout(), Layout::Monocle);
+        assert_ne!(wm.monitors.monitors[1].workspace.layout(), Layout::Monocle);
 
-                    self.monitors.focused(|_, monitor| {
-                        if monitor.workspace.find(window.id()).is_none() {
-                            monitor
-                                .workspace
-                                .insert(Client::new(window.clone(), State::from(type_.as_slice())));
-                        }
+        manage(&fake, &mut wm, 10);
 
-                        Ok(())
-                    })?;
+        wm.handle_sequence(direction(Request::FocusDir, "down")).unwrap();
 
-                    self.tile()?;
-                }
-            }
-            Event::UnmapNotify { window, .. } => {
-                log::write(format!("unmap notify: {}\n", window), Severity::Info)?;
+        assert_eq!(fake.calls().last(), Some(&Call::WarpPointer(500, 1200)));
 
-                self.monitors.all(|_, monitor| {
-                    if let Some(index) = monitor.workspace.find(window) {
-                        monitor.workspace.remove(index);
-                    }
+        manage(&fake, &mut wm, 11);
 
-                    Ok(())
-                })?;
+        assert_eq!(wm.monitors.locate(11), Some((1, 0)));
 
-                self.tile()?;
-            }
-            Event::EnterNotify { window, .. } => {
-                log::write(format!("enter notify: {}\n", window), Severity::Info)?;
+        wm.handle_sequence(direction(Request::FocusDir, "up")).unwrap();
 
-                if window != self.root.id() && window > 1 {
-                    let window = self.display.window_from_id(window)?;
-                    let ewmh = self.display.use_ewmh(&window);
+        assert_eq!(wm.backend.input_focus().unwrap(), 10);
 
-                    if !ewmh.get_wm_window_type()?.contains(&EwmhWindowType::Dock) {
-                        window.set_input_focus(RevertTo::Parent)?;
-                    }
-                }
-            }
-            Event::FocusIn { window, .. } => {
-                log::write(format!("focus in: {}\n", window), Severity::Info)?;
+        wm.handle_sequence(direction(Request::SwapDir, "down")).unwrap();
 
-                if window != self.root.id() && window > 1 {
-                    let window = self.display.window_from_id(window)?;
-                    let ewmh = self.display.use_ewmh(&window);
+        assert_eq!(wm.monitors.locate(10), Some((1, 0)));
+        assert_eq!(wm.monitors.locate(11), Some((0, 0)));
+        assert_eq!(fake.geometry(10), Area::new(0, 800, 1000, 800));
+        assert_eq!(fake.geometry(11), Area::new(0, 0, 1000, 800));
+        assert_eq!(wm.backend.input_focus().unwrap(), 10);
+    }
 
-                    if !ewmh.get_wm_window_type()?.contains(&EwmhWindowType::Dock) {
-                        self.set_border(&window)?;
-                    }
-                }
-            }
-            Event::ButtonEvent {
-                kind,
-                coordinates,
-                subwindow,
-                button,
-                ..
-            } => match kind {
-                EventKind::Press => {
-                    if !self.monitors.is_tiled(subwindow) && self.config.windows.mouse_movement {
-                        let window = self.display.window_from_id(subwindow)?;
+    #[test]
+    fn sticky_clients_float_on_every_workspace() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
 
-                        window.raise()?;
+        manage(&fake, &mut wm, 10);
 
-                        window.grab_pointer(
-                            vec![EventMask::PointerMotion, EventMask::ButtonRelease],
-                            Cursor::Nop,
-                            PointerMode::Asynchronous,
-                            KeyboardMode::Asynchronous,
-                            true,
-                            0,
-                        )?;
+        wm.set_wm_state(10, WmState::Sticky, |_| true).unwrap();
 
-                        let geometry = window.get_geometry()?;
+        assert_eq!(wm.monitors.client_mut(10).unwrap().state, State::Float);
 
-                        self.grab.replace(Grab::new(
-                            button,
-                            window,
-                            geometry,
-                            coordinates.root_x,
-                            coordinates.root_y,
-                        ));
-                    }
-                }
-                EventKind::Release => {
-                    if self.grab.is_some() {
-                        self.display.ungrab_pointer()?;
+        wm.handle_sequence(Sequence::new(Request::Workspace, 2)).unwrap();
 
-                        self.grab = None;
-                    }
-                }
-            },
-            Event::MotionNotify { coordinates, .. } => {
-                log::write(
-                    format!("motion notify: {:?}\n", coordinates),
-                    Severity::Info,
-                )?;
+        manage(&fake, &mut wm, 11);
 
-                if let Some(grab) = &mut self.grab {
-                    let x_diff = coordinates.root_x as i16 - grab.x as i16;
-                    let y_diff = coordinates.root_y as i16 - grab.y as i16;
+        assert_eq!(wm.monitors.locate(10), Some((0, 1)));
+        assert!(fake.is_mapped(10));
+        assert_eq!(fake.geometry(11), Area::new(0, 0, 1000, 800));
 
-                    match grab.button {
-                        Button::Button1 => {
-                            grab.window.mov(
-                                (grab.geometry.x as i16 + x_diff) as u16,
-                                (grab.geometry.y as i16 + y_diff) as u16,
-                            )?;
-                        }
-                        Button::Button3 => {
-                            grab.window.resize(
-                                (grab.geometry.width as i16 + x_diff) as u16,
-                                (grab.geometry.height as i16 + y_diff) as u16,
-                            )?;
-                        }
-                        _ => {}
-                    }
-                }
-            }
-            Event::ConfigureRequest { window, values } => {
-                log::write(
-                    format!("configure request: {}, values: {:?}\n", window, values),
-                    Severity::Info,
-                )?;
+        wm.handle_sequence(Sequence::new(Request::FocusDown, 0)).unwrap();
 
-                let window = self.display.window_from_id(window)?;
-                let ewmh = self.display.use_ewmh(&window);
+        assert_eq!(wm.backend.input_focus().unwrap(), 10);
 
-                if ewmh.get_wm_window_type()?.contains(&EwmhWindowType::Dock) {
-                    window.configure(ValuesBuilder::new(values))?;
-                }
-            }
-            _ => {}
-        }
+        wm.handle_sequence(Sequence::new(Request::Workspace, 1)).unwrap();
 
-        Ok(())
+        assert_eq!(wm.monitors.locate(10), Some((0, 0)));
+        assert!(!fake.is_mapped(11));
     }
 
-    pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        self.setup()?;
+    #[test]
+    fn transients_are_raised_only_with_their_parent() {
+        let (fake, mut wm) = manager(&[Area::new(0, 0, 1000, 800)]);
 
-        log::write("yaxiwm is running\n", Severity::Info)?;
+        manage(&fake, &mut wm, 10);
+        manage(&fake, &mut wm, 12);
 
-        listen(self.display.clone(), self.events.clone());
+        let (transient_for, atom) = (wm.atoms.wm_transient_for, wm.atoms.window);
 
-        // TODO: the bspwm config runs this to start the bar
-        //
-        // ~/.config/polybar/launch.sh &
+        let transient = |wid: u32, parent: u32| {
+            let window = fake.create(wid, Vec::new());
 
-        while !self.should_close {
-            match self.events.wait()? {
-                EventType::XEvent(event) => {
-                    self.handle_event(event)?;
-                },
-                EventType::Config(sequence) => {
-                    self.handle_sequence(sequence)?;
-                },
-            }
-        }
+            window.set_property(transient_for, atom, &[parent]).unwrap();
 
-        Ok(())
-    }
-}
+            window
+        };
 
-fn listen(display: Display, events: EventQueue) {
-    thread::spawn(move || {
-        loop {
-            let event = display.next_event().expect("failed to listen");
+        let dialog = transient(11, 10);
 
-            events.push(EventType::XEvent(event)).expect("failed to push");
-        }
-    });
-}
+        wm.manage(dialog, &[]).unwrap();
+        wm.set_wm_state(12, WmState::Fullscreen, |_| true).unwrap();
+
+        fake.calls();
+        wm.tile().unwrap();
 
+        let calls = fake.calls();
+
+        assert!(calls.contains(&Call::Raise(12)));
+        assert!(!calls.contains(&Call::Raise(11)));
+
+        let dialog = transient(13, 12);
+
+        wm.manage(dialog, &[]).unwrap();
+
+        fake.calls();
+        wm.tile().unwrap();
+
+        let calls = fake.calls();
+        let raised = |wid: u32| calls.iter().position(|call| *call == Call::Raise(wid));
+
+        assert!(raised(13) > raised(12));
+        assert!(!calls.contains(&Call::Raise(11)));
+    }
+}
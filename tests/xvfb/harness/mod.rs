@@ -0,0 +1,224 @@
+use proto::{Reply, Request, Sequence, Stream};
+
+use yaxi::display::{self, Display};
+use yaxi::proto::WindowClass;
+use yaxi::window::{ValuesBuilder, Window, WindowArguments, WindowKind};
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// tests run in parallel, so picking a display number and claiming it has to happen one at a time
+static DISPLAYS: Mutex<()> = Mutex::new(());
+
+static SESSIONS: AtomicU32 = AtomicU32::new(0);
+
+pub fn wait_for<F>(mut f: F) -> bool
+where
+    F: FnMut() -> bool,
+{
+    let start = Instant::now();
+
+    while start.elapsed() < TIMEOUT {
+        if f() {
+            return true;
+        }
+
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    f()
+}
+
+pub struct Xvfb {
+    process: Child,
+    display: String,
+}
+
+impl Xvfb {
+    // every screen becomes a xinerama monitor, laid out left to right
+    pub fn start(screens: &[(u16, u16)]) -> Result<Xvfb, Box<dyn std::error::Error>> {
+        let _guard = DISPLAYS.lock().map_err(|_| "failed to lock")?;
+
+        let number = (99..200)
+            .find(|number| {
+                !Path::new(&format!("/tmp/.X{number}-lock")).exists()
+                    && !Path::new(&format!("/tmp/.X11-unix/X{number}")).exists()
+            })
+            .ok_or("no free display")?;
+
+        let mut command = Command::new("Xvfb");
+
+        command
+            .arg(format!(":{number}"))
+            .args(["+xinerama", "-nolisten", "tcp"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        for (index, (width, height)) in screens.iter().enumerate() {
+            command
+                .arg("-screen")
+                .arg(index.to_string())
+                .arg(format!("{width}x{height}x24"));
+        }
+
+        let xvfb = Xvfb {
+            process: command.spawn()?,
+            display: format!(":{number}"),
+        };
+
+        if !wait_for(|| Path::new(&format!("/tmp/.X11-unix/X{number}")).exists()) {
+            return Err("Xvfb did not start".into());
+        }
+
+        Ok(xvfb)
+    }
+}
+
+impl Drop for Xvfb {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+pub struct Session {
+    display: Display,
+    root: Window,
+    home: PathBuf,
+    wm: Child,
+    _xvfb: Xvfb,
+}
+
+impl Session {
+    pub fn start(screens: &[(u16, u16)]) -> Result<Session, Box<dyn std::error::Error>> {
+        let xvfb = Xvfb::start(screens)?;
+
+        let home = env::temp_dir().join(format!(
+            "yaxiwm-{}-{}",
+            process::id(),
+            SESSIONS.fetch_add(1, Ordering::SeqCst)
+        ));
+
+        fs::create_dir_all(home.join(".config/yaxiwm"))?;
+        fs::write(home.join(".config/yaxiwm/autostart.sh"), "")?;
+
+        let wm = Command::new(env!("CARGO_BIN_EXE_yaxiwm"))
+            .env("DISPLAY", &xvfb.display)
+            .env("HOME", &home)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let display = display::open(Some(&xvfb.display))?;
+        let root = display.default_root_window()?;
+
+        let session = Session {
+            display,
+            root,
+            home,
+            wm,
+            _xvfb: xvfb,
+        };
+
+        if !wait_for(|| session.socket().exists()) {
+            return Err("yaxiwm did not start".into());
+        }
+
+        session.request(Sequence::new(Request::WorkspacePerMonitor, 3))?;
+
+        Ok(session)
+    }
+
+    fn socket(&self) -> PathBuf {
+        self.home.join(".config/yaxiwm/ipc")
+    }
+
+    pub fn request(&self, sequence: Sequence) -> Result<Reply, Box<dyn std::error::Error>> {
+        let mut stream = Stream::connect_to(self.socket())?;
+
+        stream.send(sequence)?;
+        stream.finish()?;
+
+        stream
+            .replies()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| "no reply".into())
+    }
+
+    pub fn clients(&self) -> Vec<u32> {
+        match self.request(Sequence::new(Request::QueryClients, 0)) {
+            Ok(Reply::Clients(clients)) => clients,
+            _ => Vec::new(),
+        }
+    }
+
+    // maps a plain window and waits until the window manager has picked it up
+    pub fn spawn(&self) -> Result<Window, Box<dyn std::error::Error>> {
+        let window = self.root.create_window(WindowArguments {
+            depth: self.root.depth(),
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 100,
+            class: WindowClass::InputOutput,
+            border_width: 0,
+            visual: self.root.visual(),
+            values: ValuesBuilder::new(vec![]),
+        })?;
+
+        window.map(WindowKind::Window)?;
+
+        if !wait_for(|| self.clients().contains(&window.id())) {
+            return Err(format!("window {} was not managed", window.id()).into());
+        }
+
+        Ok(window)
+    }
+
+    pub fn warp_pointer(&self, x: u16, y: u16) -> Result<(), Box<dyn std::error::Error>> {
+        self.root.warp_pointer(x, y)
+    }
+
+    pub fn geometry(&self, window: &Window) -> (u16, u16, u16, u16) {
+        window
+            .get_geometry()
+            .map(|geometry| (geometry.x, geometry.y, geometry.width, geometry.height))
+            .unwrap_or_default()
+    }
+
+    pub fn root_property(&self, name: &str, type_: &str) -> Vec<u32> {
+        self.property(&self.root, name, type_)
+    }
+
+    pub fn property(&self, window: &Window, name: &str, type_: &str) -> Vec<u32> {
+        let read = || -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+            let property = self.display.intern_atom(name, false)?;
+            let type_ = self.display.intern_atom(type_, false)?;
+
+            Ok(window
+                .get_property(property, type_, false)?
+                .chunks_exact(4)
+                .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect())
+        };
+
+        read().unwrap_or_default()
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = self.wm.kill();
+        let _ = self.wm.wait();
+        let _ = fs::remove_dir_all(&self.home);
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "xvfb")]
+
+mod harness;
+
+use harness::{wait_for, Session};
+
+use proto::{Reply, Request, Sequence};
+
+const WM_STATE_NORMAL: u32 = 1;
+const WM_STATE_ICONIC: u32 = 3;
+
+#[test]
+fn tiles_new_clients() {
+    let session = Session::start(&[(1000, 800)]).unwrap();
+
+    let first = session.spawn().unwrap();
+    let second = session.spawn().unwrap();
+
+    assert!(wait_for(|| session.geometry(&first) == (0, 0, 500, 800)));
+    assert!(wait_for(|| session.geometry(&second) == (500, 0, 500, 800)));
+
+    assert_eq!(
+        session.root_property("_NET_CLIENT_LIST", "WINDOW"),
+        vec![first.id(), second.id()]
+    );
+}
+
+#[test]
+fn switching_workspaces_keeps_clients() {
+    let session = Session::start(&[(1000, 800)]).unwrap();
+
+    let window = session.spawn().unwrap();
+
+    assert_eq!(session.request(Sequence::new(Request::Workspace, 2)).unwrap(), Reply::ok());
+
+    assert!(wait_for(|| session.root_property("_NET_CURRENT_DESKTOP", "CARDINAL") == vec![1]));
+    assert!(wait_for(|| session.property(&window, "WM_STATE", "WM_STATE").first() == Some(&WM_STATE_ICONIC)));
+
+    assert_eq!(session.request(Sequence::new(Request::Workspace, 1)).unwrap(), Reply::ok());
+
+    assert!(wait_for(|| session.property(&window, "WM_STATE", "WM_STATE").first() == Some(&WM_STATE_NORMAL)));
+    assert_eq!(session.clients(), vec![window.id()]);
+    assert_eq!(session.geometry(&window), (0, 0, 1000, 800));
+}
+
+#[test]
+fn floating_clients_leave_the_layout() {
+    let session = Session::start(&[(1000, 800)]).unwrap();
+
+    let tiled = session.spawn().unwrap();
+    let floating = session.spawn().unwrap();
+
+    assert!(wait_for(|| session.geometry(&floating) == (500, 0, 500, 800)));
+
+    // new clients are focused when they map
+    assert_eq!(session.request(Sequence::new(Request::FloatToggle, 0)).unwrap(), Reply::ok());
+
+    assert!(wait_for(|| session.geometry(&tiled) == (0, 0, 1000, 800)));
+
+    assert_eq!(session.request(Sequence::new(Request::FloatRight, 50)).unwrap(), Reply::ok());
+
+    assert!(wait_for(|| session.geometry(&floating) == (550, 0, 500, 800)));
+}
+
+#[test]
+fn monitor_circulate_moves_clients_across_monitors() {
+    let session = Session::start(&[(1000, 800), (1000, 800)]).unwrap();
+
+    session.warp_pointer(500, 400).unwrap();
+
+    let window = session.spawn().unwrap();
+
+    assert!(wait_for(|| session.geometry(&window) == (0, 0, 1000, 800)));
+
+    assert_eq!(session.request(Sequence::new(Request::MonitorCirculate, 0)).unwrap(), Reply::ok());
+
+    assert!(wait_for(|| session.geometry(&window) == (1000, 0, 1000, 800)));
+}